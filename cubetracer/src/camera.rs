@@ -88,6 +88,15 @@ impl Camera {
         self.left
     }
 
+    pub fn rotation(&self) -> Vector2<f32> {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: Vector2<f32>) {
+        self.rotation = rotation;
+        self.update_axes();
+    }
+
     pub fn reorient(&mut self, x: f32, y: f32) {
         self.rotation += Vector2::new(x, -y);
         self.rotation.y = self
@@ -151,6 +160,15 @@ impl Camera {
         self.sun_direction
     }
 
+    pub fn light_cycle(&self) -> f32 {
+        self.light_cycle
+    }
+
+    pub fn set_sun(&mut self, direction: Vector3<f32>, light_cycle: f32) {
+        self.sun_direction = direction.normalize();
+        self.light_cycle = light_cycle;
+    }
+
     fn sun_projection_matrix(&self) -> Matrix4<f32> {
         let player_chunk = Vector2::new(
             (self.origin.x as i64) >> 4,
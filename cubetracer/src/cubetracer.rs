@@ -8,7 +8,7 @@ use crate::window::*;
 
 use crate::cache_buffers::*;
 
-use world::{main_world, ChunkMesh};
+use world::{ChunkMesh, TextureList};
 
 use nalgebra::{Vector2, Vector3};
 
@@ -37,7 +37,13 @@ pub struct Cubetracer {
 }
 
 impl Cubetracer {
-    pub fn new(context: &Arc<Context>, ratio: f32, fov: f32, view_distance: usize) -> Self {
+    pub fn new(
+        context: &Arc<Context>,
+        textures_info: &TextureList,
+        ratio: f32,
+        fov: f32,
+        view_distance: usize,
+    ) -> Self {
         let camera = Camera::new(
             Vector3::new(0.0, 80.0, 0.0),
             Vector2::new(std::f32::consts::PI / 2.0, 0.0),
@@ -47,7 +53,6 @@ impl Cubetracer {
             Vector3::new(0.1, -1.0, 0.1),
         );
 
-        let textures_info = &main_world().textures;
         let (w, h) = textures_info.dimensions();
 
         let texture_array =
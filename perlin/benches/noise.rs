@@ -0,0 +1,78 @@
+use nalgebra::Vector3;
+use perlin::{NoiseSource, PerlinOctaves};
+use rand::{rngs::StdRng, SeedableRng};
+
+use std::time::{Duration, Instant};
+
+/** Grids sampled for the terrain of a chunk */
+const CHUNKS: usize = 2000;
+const RUNS: usize = 3;
+
+fn measure(mut sample: impl FnMut(usize)) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+
+            for chunk in 0..CHUNKS {
+                sample(chunk);
+            }
+
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let octaves = PerlinOctaves::new(16, &mut StdRng::seed_from_u64(42));
+
+    let size = Vector3::new(5, 33, 5);
+    let amplitude = Vector3::new(684.412, 684.412, 684.412);
+    let position = |chunk: usize| Vector3::new((chunk % 64 * 4) as f32, 0., (chunk / 64 * 4) as f32);
+
+    let mut grid = vec![0.; size.x * size.y * size.z];
+
+    let results = [
+        ("per point", measure(|chunk| {
+            let p = position(chunk);
+            let mut i = 0;
+
+            for x in 0..size.x {
+                for z in 0..size.z {
+                    for y in 0..size.y {
+                        let (x, y, z) = ((p.x + x as f32) * amplitude.x, y as f32 * amplitude.y, (p.z + z as f32) * amplitude.z);
+
+                        grid[i] = octaves.sample3d(x, y, z);
+                        i += 1;
+                    }
+                }
+            }
+        })),
+        ("noise", measure(|chunk| {
+            octaves.noise(position(chunk), size, amplitude);
+        })),
+        ("fill scalar", measure(|chunk| octaves.fill_scalar(&mut grid, position(chunk), size, amplitude))),
+        ("fill", measure(|chunk| octaves.fill(&mut grid, position(chunk), size, amplitude))),
+    ];
+
+    println!(
+        "{} grids of {}x{}x{}, 16 octaves, best of {} runs (simd: {})",
+        CHUNKS,
+        size.x,
+        size.y,
+        size.z,
+        RUNS,
+        cfg!(feature = "simd")
+    );
+
+    let samples = (CHUNKS * size.x * size.y * size.z) as f64;
+
+    for (name, time) in &results {
+        println!(
+            "{:<12} {:>7.1} Msamples/s, x{:.2}",
+            name,
+            samples / time.as_secs_f64() / 1e6,
+            results[0].1.as_secs_f64() / time.as_secs_f64()
+        );
+    }
+}
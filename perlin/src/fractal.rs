@@ -0,0 +1,117 @@
+use crate::NoiseSource;
+
+/**
+ * Shift between the octaves, so that they don't all cross zero at the origin
+ */
+const OCTAVE_SHIFT: f32 = 71.37;
+
+/**
+ * Octaves of a source, each one `lacunarity` times the frequency of the previous one and `gain`
+ * times its weight
+ */
+#[derive(Copy, Clone, Debug)]
+pub struct Octaves {
+    pub count: usize,
+    pub lacunarity: f32,
+    pub gain: f32,
+}
+
+impl Octaves {
+    pub fn new(count: usize) -> Octaves {
+        Octaves {
+            count,
+            lacunarity: 2.,
+            gain: 0.5,
+        }
+    }
+
+    /**
+     * Weighted sum of `f` applied to each octave, divided by the sum of the weights.
+     * `f` receives the value of the source and the weight given by the previous octaves
+     */
+    fn fold(&self, sample: impl Fn(f32, f32) -> f32, mut f: impl FnMut(f32) -> f32) -> f32 {
+        let mut frequency = 1.;
+        let mut amplitude = 1.;
+        let mut sum = 0.;
+        let mut total = 0.;
+
+        for i in 0..self.count {
+            sum += f(sample(frequency, i as f32 * OCTAVE_SHIFT)) * amplitude;
+            total += amplitude;
+
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+
+        if total > 0. {
+            sum / total
+        } else {
+            0.
+        }
+    }
+}
+
+/**
+ * Ridged multifractal: the absolute value of the source is inverted into sharp crests, and each
+ * octave is weighted by the previous ones so that valleys stay smooth. Meant for mountains
+ */
+pub struct Ridged<S> {
+    pub source: S,
+    pub octaves: Octaves,
+}
+
+/**
+ * Absolute value of the source: round hills separated by creases, like clouds or dunes
+ */
+pub struct Billow<S> {
+    pub source: S,
+    pub octaves: Octaves,
+}
+
+impl<S: NoiseSource> Ridged<S> {
+    pub fn new(source: S, octaves: Octaves) -> Ridged<S> {
+        Ridged { source, octaves }
+    }
+
+    fn ridge(&self, sample: impl Fn(f32, f32) -> f32) -> f32 {
+        let mut weight = 1.;
+
+        let value = self.octaves.fold(sample, |v| {
+            let signal = (1. - v.abs()).powi(2) * weight;
+            weight = (signal * 2.).clamp(0., 1.);
+
+            signal
+        });
+
+        value * 2. - 1.
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Ridged<S> {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        self.ridge(|f, shift| self.source.sample2d(x * f + shift, z * f + shift))
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.ridge(|f, shift| self.source.sample3d(x * f + shift, y * f + shift, z * f + shift))
+    }
+}
+
+impl<S: NoiseSource> Billow<S> {
+    pub fn new(source: S, octaves: Octaves) -> Billow<S> {
+        Billow { source, octaves }
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Billow<S> {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        self.octaves.fold(|f, shift| self.source.sample2d(x * f + shift, z * f + shift), |v| v.abs() * 2. - 1.)
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.octaves.fold(
+            |f, shift| self.source.sample3d(x * f + shift, y * f + shift, z * f + shift),
+            |v| v.abs() * 2. - 1.,
+        )
+    }
+}
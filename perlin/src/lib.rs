@@ -1,3 +1,14 @@
+mod noise;
 mod perlin;
+mod simplex;
+mod fractal;
+mod warp;
 
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd;
+
+pub use noise::*;
 pub use perlin::*;
+pub use simplex::*;
+pub use fractal::*;
+pub use warp::*;
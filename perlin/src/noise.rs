@@ -0,0 +1,49 @@
+/**
+ * Noise sampled point by point, so that different kinds of noise can be combined.
+ * Unless stated otherwise, values are roughly in [-1, 1]
+ */
+pub trait NoiseSource: Send + Sync {
+    fn sample2d(&self, x: f32, z: f32) -> f32;
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32;
+}
+
+impl<S: NoiseSource + ?Sized> NoiseSource for Box<S> {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        self.as_ref().sample2d(x, z)
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.as_ref().sample3d(x, y, z)
+    }
+}
+
+/**
+ * Source sampled at another frequency, with its values multiplied by `amplitude`
+ */
+pub struct Scale<S> {
+    pub source: S,
+    pub frequency: f32,
+    pub amplitude: f32,
+}
+
+impl<S: NoiseSource> Scale<S> {
+    pub fn new(source: S, frequency: f32, amplitude: f32) -> Scale<S> {
+        Scale {
+            source,
+            frequency,
+            amplitude,
+        }
+    }
+}
+
+impl<S: NoiseSource> NoiseSource for Scale<S> {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        self.source.sample2d(x * self.frequency, z * self.frequency) * self.amplitude
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let f = self.frequency;
+        self.source.sample3d(x * f, y * f, z * f) * self.amplitude
+    }
+}
@@ -2,16 +2,18 @@ use nalgebra::Vector3;
 
 use rand::{Rng, rngs::StdRng};
 
-const GRAD_1: [f32; 16] = [1., -1., 1., -1., 1., -1., 1., -1., 0., 0., 0., 0., 1., 0., -1., 0.];
-const GRAD_2: [f32; 16] = [1., 1., -1., -1., 0., 0., 0., 0., 1., -1., 1., -1., 1., -1., 1., -1.];
-const GRAD_3: [f32; 16] = [0., 0., 0., 0., 1., 1., -1., -1., 1., 1., -1., -1., 0., 1., 0., -1.];
+use crate::NoiseSource;
+
+pub(crate) const GRAD_1: [f32; 16] = [1., -1., 1., -1., 1., -1., 1., -1., 0., 0., 0., 0., 1., 0., -1., 0.];
+pub(crate) const GRAD_2: [f32; 16] = [1., 1., -1., -1., 0., 0., 0., 0., 1., -1., 1., -1., 1., -1., 1., -1.];
+pub(crate) const GRAD_3: [f32; 16] = [0., 0., 0., 0., 1., 1., -1., -1., 1., 1., -1., -1., 0., 1., 0., -1.];
 const GRAD_4: [f32; 16] = [1., -1., 1., -1., 1., -1., 1., -1., 0., 0., 0., 0., 1., 0., -1., 0.];
 const GRAD_5: [f32; 16] = [0., 0., 0., 0., 1., 1., -1., -1., 1., 1., -1., -1., 0., 1., 0., -1.];
 
 pub struct PerlinNoise
 {
-    random: Vector3<f32>,
-    perlin_values: [usize; 512],
+    pub(crate) random: Vector3<f32>,
+    pub(crate) perlin_values: [usize; 512],
 }
 
 pub struct PerlinOctaves
@@ -20,48 +22,73 @@ pub struct PerlinOctaves
 }
 
 #[inline]
-fn lerp(t: f32, a: f32, b: f32) -> f32 {
+pub(crate) fn lerp(t: f32, a: f32, b: f32) -> f32 {
     a + t * (b - a)
 }
 
 #[inline]
-fn ifloor(v: f32) -> i32 {
+pub(crate) fn ifloor(v: f32) -> i32 {
+    // casts saturate, the result is the same on every target
     let res = v as i32;
 
     if v < res as f32 {
-        res - 1
+        res.wrapping_sub(1)
     } else {
         res
     }
 }
 
 #[inline]
-fn floor(v: f32) -> (usize, f32) {
+pub(crate) fn floor(v: f32) -> (usize, f32) {
     let res = ifloor(v); 
 
     ((res & 255) as usize, v - res as f32)
 }
 
+/**
+ * Shuffled values from 0 to 255, repeated twice so that the sum of two values is a valid index
+ */
+pub(crate) fn permutations(rng: &mut StdRng) -> [usize; 512] {
+    let mut values = [0; 512];
+
+    for (i, v) in values.iter_mut().enumerate().take(256) {
+        *v = i;
+    }
+
+    for i in 0..256 {
+        // sampled as u64, usize ranges are sampled differently on 32 bits targets
+        let j = rng.gen_range(0, 256 - i as u64) as usize + i;
+
+        values.swap(i, j);
+        values[i + 256] = values[i];
+    }
+
+    values
+}
+
+/**
+ * Position and distance between the samples of an octave
+ */
+fn octave_position(position: Vector3<f32>, amplitude: Vector3<f32>, freq: f32) -> (Vector3<f32>, Vector3<f32>) {
+    let amp = amplitude * freq;
+    let mut pos = position.component_mul(&amp);
+
+    pos.x += (ifloor(pos.x) % 16777216).wrapping_sub(ifloor(pos.x)) as f32;
+    pos.x += (ifloor(pos.z) % 16777216).wrapping_sub(ifloor(pos.z)) as f32;
+
+    (pos, amp)
+}
+
 #[inline]
-fn delta(v: f32) -> f32 {
-    v.powi(3) * (v * (v * 6. - 15.) + 10.)
+pub(crate) fn delta(v: f32) -> f32 {
+    // powi has no guaranteed precision
+    v * v * v * (v * (v * 6. - 15.) + 10.)
 }
 
 impl PerlinNoise
 {
     pub fn new(rng: &mut StdRng) -> PerlinNoise {
-        let mut values = [0; 512];
-
-        for i in 0..256 {
-            values[i] = i;
-        }
-
-        for i in 0..256 {
-            let j = rng.gen_range(0, 256 - i) + i;
-
-            values.swap(i, j);
-            values[i + 256] = values[i];
-        }
+        let values = permutations(rng);
 
         PerlinNoise {
             random: Vector3::new(
@@ -83,7 +110,7 @@ impl PerlinNoise
         GRAD_1[pos] * x + GRAD_2[pos] * y + GRAD_3[pos] * z
     }
 
-    pub fn noise2d(&self, result: &mut Vec<f32>, position: Vector3<f32>,
+    pub fn noise2d(&self, result: &mut [f32], position: Vector3<f32>,
                           size: Vector3<usize>, freq: f32, amplitude: Vector3<f32>)
     {
         let freq = 1. / freq;
@@ -120,13 +147,36 @@ impl PerlinNoise
         }
     }
 
-    pub fn noise(&self, result: &mut Vec<f32>, position: Vector3<f32>,
+    pub fn noise(&self, result: &mut [f32], position: Vector3<f32>,
                           size: Vector3<usize>, freq: f32, amplitude: Vector3<f32>)
     {
         if size.y == 1 {
             return self.noise2d(result, position, size, freq, amplitude);
         }
 
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        self.noise3d_simd(result, position, size, freq, amplitude);
+
+        #[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+        self.noise3d(result, position, size, freq, amplitude);
+    }
+
+    /**
+     * Same as `noise`, without SIMD: reference implementation
+     */
+    pub fn noise_scalar(&self, result: &mut [f32], position: Vector3<f32>,
+                          size: Vector3<usize>, freq: f32, amplitude: Vector3<f32>)
+    {
+        if size.y == 1 {
+            return self.noise2d(result, position, size, freq, amplitude);
+        }
+
+        self.noise3d(result, position, size, freq, amplitude)
+    }
+
+    fn noise3d(&self, result: &mut [f32], position: Vector3<f32>,
+                          size: Vector3<usize>, freq: f32, amplitude: Vector3<f32>)
+    {
         let mut pos = 0;
         let freq = 1. / freq;
 
@@ -185,29 +235,99 @@ impl PerlinOctaves
         }
     }
 
-    pub fn noise(&self, position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>) -> Vec<f32> {
-        let mut result = vec![0.0; size.x * size.y * size.z];
+    fn accumulate<F>(&self, result: &mut [f32], position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>, noise: F)
+        where F: Fn(&PerlinNoise, &mut [f32], Vector3<f32>, Vector3<usize>, f32, Vector3<f32>)
+    {
+        assert_eq!(result.len(), size.x * size.y * size.z, "the grid doesn't match its size");
+
+        result.iter_mut().for_each(|v| *v = 0.);
         let mut freq = 1.0;
 
         for octave in &self.octaves {
-            let amp = amplitude * freq;
-            let mut pos = position.component_mul(&amp);
+            let (pos, amp) = octave_position(position, amplitude, freq);
 
-            pos.x += (-ifloor(pos.x) + ifloor(pos.x) % 16777216) as f32;
-            pos.x += (-ifloor(pos.z) + ifloor(pos.z) % 16777216) as f32;
-
-            octave.noise(&mut result, pos, size, freq, amp);
+            noise(octave, result, pos, size, freq, amp);
             freq /= 2.0;
         }
+    }
 
-        result
+    /**
+     * Fill a grid of `size` samples, ordered by x, then z, then y, and `amplitude` apart
+     */
+    pub fn fill(&self, result: &mut [f32], position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>) {
+        self.accumulate(result, position, size, amplitude, PerlinNoise::noise);
+    }
+
+    /**
+     * Same as `fill`, without SIMD: reference implementation
+     */
+    pub fn fill_scalar(&self, result: &mut [f32], position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>) {
+        self.accumulate(result, position, size, amplitude, PerlinNoise::noise_scalar);
     }
 
-    pub fn noise2d(&self, mut position: Vector3<f32>, mut size: Vector3<usize>, mut amplitude: Vector3<f32>) -> Vec<f32> {
+    /**
+     * Fill a grid of `size.x` by `size.z` samples, ordered by x then z
+     */
+    pub fn fill2d(&self, result: &mut [f32], mut position: Vector3<f32>, mut size: Vector3<usize>, mut amplitude: Vector3<f32>) {
         size.y = 1;
         position.y = 10.;
         amplitude.y = 1.;
 
-        self.noise(position, size, amplitude)
+        self.fill(result, position, size, amplitude);
+    }
+
+    pub fn noise(&self, position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>) -> Vec<f32> {
+        let mut result = vec![0.0; size.x * size.y * size.z];
+
+        self.fill(&mut result, position, size, amplitude);
+        result
+    }
+
+    pub fn noise2d(&self, position: Vector3<f32>, size: Vector3<usize>, amplitude: Vector3<f32>) -> Vec<f32> {
+        let mut result = vec![0.0; size.x * size.z];
+
+        self.fill2d(&mut result, position, size, amplitude);
+        result
+    }
+}
+
+impl NoiseSource for PerlinNoise {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        let mut result = [0.];
+        self.noise2d(&mut result, Vector3::new(x, 0., z), Vector3::new(1, 1, 1), 1., Vector3::new(1., 1., 1.));
+
+        result[0]
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let mut result = [0.];
+        self.noise3d(&mut result, Vector3::new(x, y, z), Vector3::new(1, 1, 1), 1., Vector3::new(1., 1., 1.));
+
+        result[0]
+    }
+}
+
+/**
+ * Same values as `noise2d` with an amplitude of 1, and as `noise` for the first sample of each
+ * column (the grid reuses its gradients along y). Not normalized: the lowest octaves have the
+ * highest weight
+ */
+impl NoiseSource for PerlinOctaves {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        self.noise2d(Vector3::new(x, 0., z), Vector3::new(1, 1, 1), Vector3::new(1., 1., 1.))[0]
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let mut result = [0.];
+        let mut freq = 1.0;
+
+        for octave in &self.octaves {
+            let (pos, amp) = octave_position(Vector3::new(x, y, z), Vector3::new(1., 1., 1.), freq);
+
+            octave.noise3d(&mut result, pos, Vector3::new(1, 1, 1), freq, amp);
+            freq /= 2.0;
+        }
+
+        result[0]
     }
 }
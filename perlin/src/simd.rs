@@ -0,0 +1,160 @@
+use nalgebra::Vector3;
+
+use std::arch::x86_64::*;
+use std::ops::{Add, Mul, Sub};
+
+use crate::{delta, floor, PerlinNoise, GRAD_1, GRAD_2, GRAD_3};
+
+const LANES: usize = 4;
+
+/**
+ * Four f32 in a SSE2 register (always available on x86_64). Only uses the operations of the
+ * scalar code in the same order, so that results are bit-identical
+ */
+#[derive(Copy, Clone)]
+struct F32x4(__m128);
+
+impl F32x4 {
+    #[inline]
+    fn splat(v: f32) -> F32x4 {
+        unsafe { F32x4(_mm_set1_ps(v)) }
+    }
+
+    #[inline]
+    fn new(values: [f32; LANES]) -> F32x4 {
+        unsafe { F32x4(_mm_loadu_ps(values.as_ptr())) }
+    }
+
+    #[inline]
+    fn to_array(self) -> [f32; LANES] {
+        let mut values = [0.; LANES];
+        unsafe { _mm_storeu_ps(values.as_mut_ptr(), self.0) };
+
+        values
+    }
+}
+
+impl Add for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn add(self, other: F32x4) -> F32x4 {
+        unsafe { F32x4(_mm_add_ps(self.0, other.0)) }
+    }
+}
+
+impl Sub for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn sub(self, other: F32x4) -> F32x4 {
+        unsafe { F32x4(_mm_sub_ps(self.0, other.0)) }
+    }
+}
+
+impl Mul for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn mul(self, other: F32x4) -> F32x4 {
+        unsafe { F32x4(_mm_mul_ps(self.0, other.0)) }
+    }
+}
+
+#[inline]
+fn lerp(t: F32x4, a: F32x4, b: F32x4) -> F32x4 {
+    a + t * (b - a)
+}
+
+#[inline]
+fn delta4(v: F32x4) -> F32x4 {
+    v * v * v * (v * (v * F32x4::splat(6.) - F32x4::splat(15.)) + F32x4::splat(10.))
+}
+
+#[inline]
+fn gradient(hashes: [usize; LANES], x: F32x4, y: F32x4, z: F32x4) -> F32x4 {
+    let table = |grad: &[f32; 16]| F32x4::new([grad[hashes[0] & 15], grad[hashes[1] & 15], grad[hashes[2] & 15], grad[hashes[3] & 15]]);
+
+    table(&GRAD_1) * x + table(&GRAD_2) * y + table(&GRAD_3) * z
+}
+
+impl PerlinNoise {
+    /**
+     * 3D noise computed on four columns of the grid at once. As y only depends on the row, the
+     * gradients of the four columns are refreshed together
+     */
+    pub(crate) fn noise3d_simd(&self, result: &mut [f32], position: Vector3<f32>,
+                          size: Vector3<usize>, freq: f32, amplitude: Vector3<f32>)
+    {
+        let freq = F32x4::splat(1. / freq);
+        let columns = size.x * size.z;
+
+        let p = &self.perlin_values;
+        let one = F32x4::splat(1.);
+
+        for first in (0..columns).step_by(LANES) {
+            // the last lanes repeat the last column when there are less than 4 columns left
+            let lanes = (columns - first).min(LANES);
+
+            let mut norm_x = [0; LANES];
+            let mut norm_z = [0; LANES];
+            let mut xs = [0.; LANES];
+            let mut zs = [0.; LANES];
+
+            for lane in 0..LANES {
+                let column = first + lane.min(lanes - 1);
+                let (dx, dz) = (column / size.z, column % size.z);
+
+                let (nx, x) = floor(position.x + dx as f32 * amplitude.x + self.random.x);
+                let (nz, z) = floor(position.z + dz as f32 * amplitude.z + self.random.z);
+
+                norm_x[lane] = nx;
+                norm_z[lane] = nz;
+                xs[lane] = x;
+                zs[lane] = z;
+            }
+
+            let (x, z) = (F32x4::new(xs), F32x4::new(zs));
+            let (delta_x, delta_z) = (delta4(x), delta4(z));
+
+            let mut last_y = 1000;
+            let mut cache = [F32x4::splat(0.); 4];
+
+            for dy in 0..size.y {
+                let (norm_y, y) = floor(position.y + dy as f32 * amplitude.y + self.random.y);
+                let delta_y = F32x4::splat(delta(y));
+                let y = F32x4::splat(y);
+
+                if dy == 0 || norm_y != last_y {
+                    last_y = norm_y;
+
+                    let mut hashes = [[0; LANES]; 8];
+
+                    for lane in 0..LANES {
+                        let a = p[norm_x[lane]] + norm_y;
+                        let b = p[a] + norm_z[lane];
+                        let c = p[a + 1] + norm_z[lane];
+                        let d = p[norm_x[lane] + 1] + norm_y;
+                        let e = p[d] + norm_z[lane];
+                        let f = p[d + 1] + norm_z[lane];
+
+                        for (i, &h) in [b, e, c, f, b + 1, e + 1, c + 1, f + 1].iter().enumerate() {
+                            hashes[i][lane] = p[h];
+                        }
+                    }
+
+                    cache[0] = lerp(delta_x, gradient(hashes[0], x, y, z), gradient(hashes[1], x - one, y, z));
+                    cache[1] = lerp(delta_x, gradient(hashes[2], x, y - one, z), gradient(hashes[3], x - one, y - one, z));
+                    cache[2] = lerp(delta_x, gradient(hashes[4], x, y, z - one), gradient(hashes[5], x - one, y, z - one));
+                    cache[3] = lerp(delta_x, gradient(hashes[6], x, y - one, z - one), gradient(hashes[7], x - one, y - one, z - one));
+                }
+
+                let value = lerp(delta_z, lerp(delta_y, cache[0], cache[1]), lerp(delta_y, cache[2], cache[3])) * freq;
+
+                for (lane, v) in value.to_array().iter().enumerate().take(lanes) {
+                    result[(first + lane) * size.y + dy] += v;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::NoiseSource;
+
+/**
+ * Offsets between the samples of the warp source used for each axis
+ */
+const AXIS_SHIFTS: [f32; 3] = [0., 113.7, 227.3];
+
+/**
+ * Source sampled at coordinates moved by another noise, up to `strength` on each axis: bends
+ * straight features into meanders and swirls
+ */
+pub struct DomainWarp<S, W> {
+    pub source: S,
+    pub warp: W,
+    pub strength: f32,
+}
+
+impl<S: NoiseSource, W: NoiseSource> DomainWarp<S, W> {
+    pub fn new(source: S, warp: W, strength: f32) -> DomainWarp<S, W> {
+        DomainWarp {
+            source,
+            warp,
+            strength,
+        }
+    }
+}
+
+impl<S: NoiseSource, W: NoiseSource> NoiseSource for DomainWarp<S, W> {
+    fn sample2d(&self, x: f32, z: f32) -> f32 {
+        let [a, _, b] = AXIS_SHIFTS;
+
+        let dx = self.warp.sample2d(x + a, z + a);
+        let dz = self.warp.sample2d(x + b, z + b);
+
+        self.source.sample2d(x + dx * self.strength, z + dz * self.strength)
+    }
+
+    fn sample3d(&self, x: f32, y: f32, z: f32) -> f32 {
+        let [a, b, c] = AXIS_SHIFTS;
+
+        let dx = self.warp.sample3d(x + a, y + a, z + a);
+        let dy = self.warp.sample3d(x + b, y + b, z + b);
+        let dz = self.warp.sample3d(x + c, y + c, z + c);
+
+        self.source.sample3d(x + dx * self.strength, y + dy * self.strength, z + dz * self.strength)
+    }
+}
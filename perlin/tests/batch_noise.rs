@@ -0,0 +1,48 @@
+use nalgebra::Vector3;
+use perlin::PerlinOctaves;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn bits(values: &[f32]) -> Vec<u32> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+/**
+ * With the `simd` feature, `fill` uses the SIMD implementation: run the tests with and without it
+ */
+#[test]
+fn batch_is_bit_identical_to_scalar() {
+    let octaves = PerlinOctaves::new(16, &mut StdRng::seed_from_u64(42));
+
+    let grids = [
+        // terrain and caves of a chunk
+        (Vector3::new(-64., 0., 128.), Vector3::new(5, 33, 5), Vector3::new(684.412, 684.412, 684.412)),
+        (Vector3::new(-64., 0., 128.), Vector3::new(5, 33, 5), Vector3::new(8.555_15, 4.277_575, 8.555_15)),
+        (Vector3::new(3.25, -7., 1e5), Vector3::new(7, 9, 3), Vector3::new(0.4, 0.13, 1.7)),
+        (Vector3::new(0., 0., 0.), Vector3::new(1, 2, 1), Vector3::new(1., 1., 1.)),
+    ];
+
+    for &(position, size, amplitude) in &grids {
+        let mut batch = vec![f32::NAN; size.x * size.y * size.z];
+        let mut scalar = vec![0.; size.x * size.y * size.z];
+
+        octaves.fill(&mut batch, position, size, amplitude);
+        octaves.fill_scalar(&mut scalar, position, size, amplitude);
+
+        assert_eq!(bits(&batch), bits(&scalar));
+        assert_eq!(bits(&octaves.noise(position, size, amplitude)), bits(&scalar));
+    }
+}
+
+#[test]
+fn batch_fills_2d_grids() {
+    let octaves = PerlinOctaves::new(8, &mut StdRng::seed_from_u64(7));
+
+    let position = Vector3::new(16., 0., -32.);
+    let size = Vector3::new(5, 33, 5);
+    let amplitude = Vector3::new(200., 0., 200.);
+
+    let mut grid = vec![0.; 25];
+    octaves.fill2d(&mut grid, position, size, amplitude);
+
+    assert_eq!(bits(&grid), bits(&octaves.noise2d(position, size, amplitude)));
+}
@@ -0,0 +1,87 @@
+use nalgebra::Vector3;
+use perlin::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn rng() -> StdRng {
+    SeedableRng::seed_from_u64(42)
+}
+
+/**
+ * Samples on a grid of 64x64 points, with a few points per cell of the noise
+ */
+fn grid(source: &impl NoiseSource) -> Vec<f32> {
+    (0..64 * 64)
+        .map(|i| source.sample3d((i % 64) as f32 * 0.37 - 11., 3.1, (i / 64) as f32 * 0.37 - 11.))
+        .collect()
+}
+
+fn assert_normalized(values: &[f32]) {
+    assert!(values.iter().all(|v| (-1.0..=1.0).contains(v)));
+
+    // not constant
+    let min = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    assert!(max - min > 0.5);
+}
+
+#[test]
+fn octaves_samples_match_grid_noise() {
+    let octaves = PerlinOctaves::new(4, &mut rng());
+
+    let position = Vector3::new(-3.5, 2., 7.25);
+    let amplitude = Vector3::new(0.3, 0.7, 0.2);
+    let size = Vector3::new(3, 4, 5);
+
+    let noise = octaves.noise(position, size, amplitude);
+    let noise2d = octaves.noise2d(position, size, amplitude);
+
+    let mut i = 0;
+
+    for x in 0..size.x {
+        for z in 0..size.z {
+            // the grid samples (position + offset) * amplitude
+            let (px, pz) = ((position.x + x as f32) * amplitude.x, (position.z + z as f32) * amplitude.z);
+
+            assert!((octaves.sample2d(px, pz) - noise2d[x * size.z + z]).abs() < 1e-4);
+
+            for y in 0..size.y {
+                let py = (position.y + y as f32) * amplitude.y;
+
+                // the grid reuses the gradients of the first sample of each column
+                if y == 0 {
+                    assert!((octaves.sample3d(px, py, pz) - noise[i]).abs() < 1e-4);
+                }
+                i += 1;
+            }
+        }
+    }
+}
+
+#[test]
+fn sources_are_normalized() {
+    let simplex = || SimplexNoise::new(&mut rng());
+
+    assert_normalized(&grid(&simplex()));
+    assert_normalized(&(0..4096).map(|i| simplex().sample2d(i as f32 * 0.37, 5.)).collect::<Vec<_>>());
+
+    assert_normalized(&grid(&Ridged::new(simplex(), Octaves::new(5))));
+    assert_normalized(&grid(&Billow::new(simplex(), Octaves::new(5))));
+    assert_normalized(&grid(&DomainWarp::new(simplex(), Scale::new(simplex(), 0.5, 1.), 4.)));
+}
+
+#[test]
+fn sources_can_be_mixed() {
+    let sources: Vec<Box<dyn NoiseSource>> = vec![
+        Box::new(SimplexNoise::new(&mut rng())),
+        Box::new(Ridged::new(Box::new(PerlinNoise::new(&mut rng())) as Box<dyn NoiseSource>, Octaves::new(3))),
+        Box::new(Scale::new(PerlinOctaves::new(4, &mut rng()), 0.1, 0.25)),
+    ];
+
+    let mix = |x, z| sources.iter().map(|s| s.sample2d(x, z)).sum::<f32>();
+
+    assert_eq!(mix(12.5, -3.25), mix(12.5, -3.25));
+    assert_ne!(mix(12.5, -3.25), mix(13.5, -3.25));
+
+    // same seed, same values
+    assert_eq!(grid(&SimplexNoise::new(&mut rng())), grid(&SimplexNoise::new(&mut rng())));
+}
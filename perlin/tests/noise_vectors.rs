@@ -0,0 +1,44 @@
+use nalgebra::Vector3;
+use perlin::{NoiseSource, PerlinOctaves, SimplexNoise};
+use rand::{rngs::StdRng, SeedableRng};
+
+/**
+ * Exact outputs for a known seed, as f32 bits: they must be the same on every target
+ */
+fn bits(values: &[f32]) -> Vec<u32> {
+    values.iter().map(|v| v.to_bits()).collect()
+}
+
+#[test]
+fn perlin_octaves_vectors() {
+    let octaves = PerlinOctaves::new(4, &mut StdRng::seed_from_u64(42));
+
+    let position = Vector3::new(-3.5, 2., 7.25);
+    let size = Vector3::new(2, 3, 2);
+    let amplitude = Vector3::new(0.3, 0.7, 0.2);
+
+    assert_eq!(
+        bits(&octaves.noise(position, size, amplitude)),
+        [
+            0x3f9d_da5b, 0x4001_a175, 0x3ffc_604b, 0x3fa9_4a44, 0x3fde_0ba3, 0x3ffc_9289,
+            0x3f2f_25da, 0x3fb9_5a04, 0x3f95_7e2a, 0x3f72_6a29, 0x3fa1_8667, 0x3f8f_7227,
+        ]
+    );
+
+    assert_eq!(
+        bits(&octaves.noise2d(position, size, amplitude)),
+        [0x404d_f425, 0x4053_42b7, 0x4033_18ac, 0x4039_f16e]
+    );
+}
+
+#[test]
+fn simplex_vectors() {
+    let simplex = SimplexNoise::new(&mut StdRng::seed_from_u64(42));
+    let points = [(0.5, -1.25, 3.75), (-100.3, 7.1, 12.9), (1e4, -2e3, 0.1)];
+
+    let samples2d: Vec<f32> = points.iter().map(|&(x, _, z)| simplex.sample2d(x, z)).collect();
+    let samples3d: Vec<f32> = points.iter().map(|&(x, y, z)| simplex.sample3d(x, y, z)).collect();
+
+    assert_eq!(bits(&samples2d), [0xbf2a_f91c, 0xbf20_855d, 0x3ed0_7cc1]);
+    assert_eq!(bits(&samples3d), [0x3ed7_8326, 0x3e27_691e, 0xbf70_3251]);
+}
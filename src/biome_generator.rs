@@ -1,8 +1,8 @@
-use world::generator::layers::Layer;
+use world::generator::layers::LayerConfig;
 use world::BiomeType;
 
-pub fn generate_biome(seed: isize) -> Result<(), Box<dyn std::error::Error>> {
-    let (_, mut generator) = Layer::create_generator(seed);
+pub fn generate_biome(seed: i64, layers: &LayerConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, mut generator) = layers.build(seed)?.into_generators();
 
     let biome_count = 1;
     let width = biome_count * 16;
@@ -6,7 +6,8 @@ use std::sync::RwLock;
 use std::collections::HashSet;
 use std::path::Path;
 
-use world::{create_main_world, ChunkListener, World};
+use world::generator::DEFAULT_GENERATOR;
+use world::{create_world, ChunkListener, World, WorldMetadata};
 
 pub struct DumpChunkListener {
     pub loaded_chunks: RwLock<Vec<(i32, i32)>>,
@@ -53,7 +54,7 @@ impl ChunkListener for DumpChunkListener {
 }
 
 pub fn dump_map(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
-    let seed = args.value_of("seed").unwrap_or("0").parse::<isize>()?;
+    let seed = args.value_of("seed").unwrap_or("0").parse::<i64>()?;
     let folder = Path::new(args.value_of("folder").unwrap_or("./map_dump"));
     let view_distance = args
         .value_of("view-distance")
@@ -62,17 +63,24 @@ pub fn dump_map(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
 
     std::fs::create_dir_all(&folder)?;
 
-    let world = create_main_world("worldp", seed, false);
+    // chunks are generated in a throwaway world, saved worlds keep their own seed
+    let world_path = std::env::temp_dir().join(format!("map-dump-{}", std::process::id()));
+    let metadata = WorldMetadata::new(seed, DEFAULT_GENERATOR);
+    let world = create_world(world_path.to_str().ok_or("invalid temporary folder")?, &metadata)?;
     let mut listener = DumpChunkListener::new();
 
-    let mut player = world.create_player(&mut listener, view_distance);
+    let spawn = metadata.spawn;
+    let mut player = world
+        .write()
+        .unwrap()
+        .create_player(&mut listener, view_distance, spawn);
     let mut known = HashSet::new();
 
     let max = (2 * view_distance).pow(2);
 
-    while listener.update_renderer(&world, &folder, &mut known)? < max {
+    while listener.update_renderer(&world.read().unwrap(), &folder, &mut known)? < max {
         player.update(
-            world,
+            &mut world.write().unwrap(),
             &mut listener,
             Vector3::z(),
             Vector3::x(),
@@ -81,5 +89,9 @@ pub fn dump_map(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
         );
     }
 
+    drop(player);
+    drop(world);
+    std::fs::remove_dir_all(&world_path)?;
+
     Ok(())
 }
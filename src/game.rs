@@ -5,11 +5,11 @@ use winit::event_loop::EventLoop;
 use utils::framecounter::FrameCounter;
 use utils::wininput;
 
-use world::{create_main_world, main_world, ChunkListener, ChunkMesherClient, PlayerInput};
+use world::{create_world, ChunkListener, ChunkMesherClient, PlayerInput, World, WorldMetadata};
 
 use crate::config::*;
 
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 use cubetracer::context::Context;
 use cubetracer::window::*;
@@ -101,24 +101,30 @@ pub struct BaseApp {
     tracer: cubetracer::Cubetracer,
 
     chunk_mesher_client: ChunkMesherClient,
+    world: Arc<RwLock<World>>,
     player: world::Player,
     update_shadow_map: bool,
+
+    world_path: String,
+    metadata: WorldMetadata,
 }
 
 impl BaseApp {
     pub fn run(
         world_path: &str,
-        seed: isize,
-        flat: bool,
+        metadata: WorldMetadata,
         view_distance: usize,
         config: Config,
         layout: Layout,
-    ) {
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // --- World SetUp --
         let mut listener = MyChunkListener::new();
 
-        let world = create_main_world(world_path, seed, flat);
-        let player = world.create_player(&mut listener, view_distance);
+        let world = create_world(world_path, &metadata)?;
+        let player = world
+            .write()
+            .unwrap()
+            .create_player(&mut listener, view_distance, metadata.player_position);
 
         let event_loop = winit::event_loop::EventLoop::new();
 
@@ -159,13 +165,17 @@ impl BaseApp {
 
         let in_flight_frames = Self::create_sync_objects(context.device());
 
-        let tracer = cubetracer::Cubetracer::new(
+        let mut tracer = cubetracer::Cubetracer::new(
             &context,
+            &world.read().unwrap().textures,
             16. / 9.,
             FOV_RANGE.start + (FOV_RANGE.end - FOV_RANGE.start) / 2.,
             view_distance,
         );
 
+        tracer.camera_mut().set_rotation(metadata.player_rotation);
+        tracer.camera_mut().set_sun(metadata.sun_direction, metadata.light_cycle);
+
         let game = Self {
             config,
             window,
@@ -183,11 +193,32 @@ impl BaseApp {
 
             tracer,
 
-            chunk_mesher_client: ChunkMesherClient::new(),
+            chunk_mesher_client: ChunkMesherClient::new(world.clone()),
+            world,
             update_shadow_map: true,
             player,
+
+            world_path: world_path.to_string(),
+            metadata,
         };
         game.process_event(event_loop);
+
+        Ok(())
+    }
+
+    fn save_world(&mut self) {
+        let camera = self.tracer.camera();
+
+        self.metadata.player_position = self.player.position();
+        self.metadata.player_rotation = camera.rotation();
+        self.metadata.sun_direction = camera.sun_direction();
+        self.metadata.light_cycle = camera.light_cycle();
+
+        if let Err(e) = self.metadata.save(&self.world_path) {
+            log::error!("can't save world metadata: {}", e);
+        }
+
+        World::save(&self.world);
     }
 
     fn find_depth_format(context: &Context) -> vk::Format {
@@ -246,7 +277,10 @@ impl BaseApp {
                 let delta_time = frame_counter.delta_time();
 
                 match event {
-                    winit::event::Event::LoopDestroyed => return,
+                    winit::event::Event::LoopDestroyed => {
+                        self.save_world();
+                        return;
+                    }
                     winit::event::Event::MainEventsCleared => {
                         self.input_handler.update_time(delta_time);
 
@@ -322,8 +356,12 @@ impl BaseApp {
                         }
 
                         // --- Update States ---
+                        for error in self.world.read().unwrap().errors() {
+                            log::error!("{}", error);
+                        }
+
                         self.player.update(
-                            main_world(),
+                            &mut self.world.write().unwrap(),
                             &mut listener,
                             self.tracer.camera().forward(),
                             self.tracer.camera().left(),
@@ -357,7 +395,7 @@ impl BaseApp {
                         // - Cube Tracer -
 
                         /* FIXME send it to RTX
-                        let highlighted_block = match self.player.looked_block(&main_world(), self.tracer.camera().forward()) {
+                        let highlighted_block = match self.player.looked_block(&self.world.read().unwrap(), self.tracer.camera().forward()) {
                             Some((b, _)) => b,
                             _ => Vector3::new(0, -100, 0),
                         };
@@ -0,0 +1,264 @@
+use clap::ArgMatches;
+
+use std::path::Path;
+
+use world::generator::layers::{LayerConfig, LayerStage};
+use world::BiomeType;
+
+type Color = (u8, u8, u8);
+
+const BACKGROUND: Color = (32, 32, 32);
+const TEXT: Color = (255, 255, 255);
+const NO_VALUE: Color = (48, 48, 48);
+
+/** Size of the pixels of the text */
+const TEXT_SCALE: usize = 2;
+const CHAR_WIDTH: usize = 4 * TEXT_SCALE;
+const LABEL_HEIGHT: usize = 7 * TEXT_SCALE;
+const MARGIN: usize = 8;
+
+const LEGEND_WIDTH: usize = 20 * CHAR_WIDTH;
+
+/**
+ * 3x5 glyphs, rows from top to bottom
+ */
+const FONT: &[(char, u16)] = &[
+    ('A', 0b010_101_111_101_101),
+    ('B', 0b110_101_110_101_110),
+    ('C', 0b011_100_100_100_011),
+    ('D', 0b110_101_101_101_110),
+    ('E', 0b111_100_110_100_111),
+    ('F', 0b111_100_110_100_100),
+    ('G', 0b011_100_101_101_011),
+    ('H', 0b101_101_111_101_101),
+    ('I', 0b111_010_010_010_111),
+    ('J', 0b001_001_001_101_010),
+    ('K', 0b101_101_110_101_101),
+    ('L', 0b100_100_100_100_111),
+    ('M', 0b101_111_111_101_101),
+    ('N', 0b110_101_101_101_101),
+    ('O', 0b010_101_101_101_010),
+    ('P', 0b110_101_110_100_100),
+    ('Q', 0b010_101_101_110_011),
+    ('R', 0b110_101_110_101_101),
+    ('S', 0b011_100_010_001_110),
+    ('T', 0b111_010_010_010_010),
+    ('U', 0b101_101_101_101_111),
+    ('V', 0b101_101_101_101_010),
+    ('W', 0b101_101_111_111_101),
+    ('X', 0b101_101_010_101_101),
+    ('Y', 0b101_101_010_010_010),
+    ('Z', 0b111_001_010_100_111),
+    ('0', 0b111_101_101_101_111),
+    ('1', 0b010_110_010_010_111),
+    ('2', 0b110_001_010_100_111),
+    ('3', 0b110_001_010_001_110),
+    ('4', 0b101_101_111_001_001),
+    ('5', 0b111_100_110_001_110),
+    ('6', 0b011_100_111_101_111),
+    ('7', 0b111_001_010_010_010),
+    ('8', 0b111_101_111_101_111),
+    ('9', 0b111_101_111_001_110),
+    ('_', 0b000_000_000_000_111),
+    ('-', 0b000_000_111_000_000),
+    ('.', 0b000_000_000_000_010),
+    (':', 0b000_010_000_010_000),
+    ('/', 0b001_001_010_100_100),
+];
+
+struct Image {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Image {
+        let mut image = Image {
+            width,
+            height,
+            data: vec![0; width * height * 3],
+        };
+
+        image.rect(0, 0, width, height, BACKGROUND);
+        image
+    }
+
+    fn set(&mut self, x: usize, y: usize, (r, g, b): Color) {
+        if x < self.width && y < self.height {
+            let pos = (y * self.width + x) * 3;
+
+            self.data[pos] = r;
+            self.data[pos + 1] = g;
+            self.data[pos + 2] = b;
+        }
+    }
+
+    fn rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Color) {
+        for py in y..y + height {
+            for px in x..x + width {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    /**
+     * Write a text, truncated to `max_width` pixels
+     */
+    fn text(&mut self, x: usize, y: usize, text: &str, max_width: usize) {
+        for (i, c) in text.to_uppercase().chars().take(max_width / CHAR_WIDTH).enumerate() {
+            let glyph = FONT.iter().find(|(g, _)| *g == c).map_or(0, |(_, bits)| *bits);
+
+            for row in 0..5 {
+                for col in 0..3 {
+                    if glyph & (1 << (14 - row * 3 - col)) != 0 {
+                        self.rect(x + (i * 4 + col) * TEXT_SCALE, y + row * TEXT_SCALE, TEXT_SCALE, TEXT_SCALE, TEXT);
+                    }
+                }
+            }
+        }
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        image::save_buffer(path, &self.data, self.width as u32, self.height as u32, image::ColorType::Rgb8)?;
+        Ok(())
+    }
+}
+
+/**
+ * Dumped area, in blocks
+ */
+struct Region {
+    x: isize,
+    z: isize,
+    size: usize,
+    /** Pixels of the side of a dumped stage */
+    tile: usize,
+}
+
+/**
+ * Biomes are drawn with their color, other values (land, climates, noise) with arbitrary colors
+ */
+fn value_color(biomes: bool, value: isize) -> Color {
+    if biomes && value >= 0 && value < BiomeType::count() {
+        return BiomeType::from_id(value).color();
+    }
+
+    match value {
+        v if v < 0 => NO_VALUE,
+        0 => BiomeType::Ocean.color(),
+        v => {
+            let hash = (v as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            ((hash >> 56) as u8 | 0x40, (hash >> 48) as u8 | 0x40, (hash >> 40) as u8 | 0x40)
+        }
+    }
+}
+
+fn draw_stage(image: &mut Image, x: usize, y: usize, index: usize, stage: &mut LayerStage, region: &Region) {
+    let scale = stage.scale as isize;
+
+    let start = (region.x.div_euclid(scale), region.z.div_euclid(scale));
+    let end = (
+        (region.x + region.size as isize - 1).div_euclid(scale),
+        (region.z + region.size as isize - 1).div_euclid(scale),
+    );
+
+    let mut result = stage.layer.generate(
+        start.0,
+        start.1,
+        (end.0 - start.0 + 1) as usize,
+        (end.1 - start.1 + 1) as usize,
+    );
+
+    image.text(x, y, &format!("{} {} 1:{}", index, stage.name, stage.scale), region.tile);
+
+    for py in 0..region.tile {
+        for px in 0..region.tile {
+            let bx = region.x + (px * region.size / region.tile) as isize;
+            let bz = region.z + (py * region.size / region.tile) as isize;
+
+            let value = *result.at(bx.div_euclid(scale) - start.0, bz.div_euclid(scale) - start.1);
+            image.set(x + px, y + LABEL_HEIGHT + py, value_color(stage.biomes, value));
+        }
+    }
+}
+
+fn legend_height(width: usize) -> usize {
+    let columns = (width / LEGEND_WIDTH).max(1);
+    let rows = (BiomeType::count() as usize + columns - 1) / columns;
+
+    rows * LABEL_HEIGHT + MARGIN
+}
+
+/**
+ * Colors of the biomes, below the given height
+ */
+fn draw_legend(image: &mut Image, y: usize) {
+    let columns = (image.width / LEGEND_WIDTH).max(1);
+
+    for id in 0..BiomeType::count() {
+        let biome = BiomeType::from_id(id);
+
+        let x = MARGIN + (id as usize % columns) * LEGEND_WIDTH;
+        let y = y + (id as usize / columns) * LABEL_HEIGHT;
+
+        image.rect(x, y, 5 * TEXT_SCALE, 5 * TEXT_SCALE, biome.color());
+        image.text(x + CHAR_WIDTH * 2, y, biome.name(), LEGEND_WIDTH - CHAR_WIDTH * 3);
+    }
+}
+
+/**
+ * Render every stage of a biome layer graph on the same area: either as a sheet, or as a sequence
+ * of images in a folder (one per stage, plus the legend)
+ */
+pub fn dump_layers(args: &ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let seed = args.value_of("seed").unwrap_or("0").parse::<i64>()?;
+    let layers = LayerConfig::load(args.value_of("layers").unwrap_or("default"))?;
+
+    let size = args.value_of("size").unwrap_or("1024").parse::<usize>()?.max(1);
+    let region = Region {
+        x: args.value_of("x").map_or(Ok(-(size as isize) / 2), |v| v.parse::<isize>())?,
+        z: args.value_of("z").map_or(Ok(-(size as isize) / 2), |v| v.parse::<isize>())?,
+        size,
+        tile: args.value_of("tile").unwrap_or("256").parse::<usize>()?.max(1),
+    };
+
+    let mut graph = layers.build(seed)?;
+    let stages = graph.stages_mut();
+
+    let cell_width = region.tile + MARGIN;
+    let cell_height = LABEL_HEIGHT + region.tile + MARGIN;
+
+    if let Some(folder) = args.value_of("sequence") {
+        let folder = Path::new(folder);
+        std::fs::create_dir_all(folder)?;
+
+        for (i, stage) in stages.iter_mut().enumerate() {
+            let mut image = Image::new(cell_width + MARGIN, cell_height + MARGIN);
+
+            draw_stage(&mut image, MARGIN, MARGIN, i, stage, &region);
+            image.save(&folder.join(format!("{:02}_{}.png", i, stage.name)))?;
+        }
+
+        let width = region.tile.max(LEGEND_WIDTH) + 2 * MARGIN;
+        let mut legend = Image::new(width, legend_height(width) + MARGIN);
+
+        draw_legend(&mut legend, MARGIN);
+        legend.save(&folder.join("legend.png"))?;
+    } else {
+        let columns = args.value_of("columns").unwrap_or("8").parse::<usize>()?.max(1).min(stages.len());
+        let rows = (stages.len() + columns - 1) / columns;
+
+        let width = (columns * cell_width + MARGIN).max(LEGEND_WIDTH + 2 * MARGIN);
+        let mut image = Image::new(width, rows * cell_height + MARGIN + legend_height(width));
+
+        for (i, stage) in stages.iter_mut().enumerate() {
+            draw_stage(&mut image, MARGIN + (i % columns) * cell_width, MARGIN + (i / columns) * cell_height, i, stage, &region);
+        }
+
+        draw_legend(&mut image, rows * cell_height + MARGIN);
+        image.save(Path::new(args.value_of("output").unwrap_or("layers.png")))?;
+    }
+
+    Ok(())
+}
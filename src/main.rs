@@ -4,18 +4,29 @@ extern crate clap;
 mod biome_generator;
 pub mod config;
 mod dump;
+mod layer_dump;
 mod game;
 
 use clap::App;
 use config::*;
 
+use world::generator::layers::LayerConfig;
+use world::generator::{terrain_generators, FlatPreset, TerrainSettings, DEFAULT_GENERATOR};
+use world::WorldMetadata;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let conf = load_yaml!("cli.yaml");
 
     let matches = App::from_yaml(conf).get_matches();
 
+    for name in &["play", "render_chunks", "dump_layers"] {
+        if let Some(path) = matches.subcommand_matches(name).and_then(|args| args.value_of("biomes")) {
+            world::load_biomes(path)?;
+        }
+    }
+
     if let Some(args) = matches.subcommand_matches("play") {
-        let seed = args.value_of("seed").unwrap_or("0").parse::<isize>()?;
+        let seed = args.value_of("seed").unwrap_or("0").parse::<i64>()?;
         let view_distance = args
             .value_of("view-distance")
             .unwrap_or("10")
@@ -29,25 +40,63 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .min(10.0);*/
         let world_path = args.value_of("world").unwrap_or("worldp");
         let layout = Layout::parse(args.value_of("layout").unwrap_or("fr"));
-        let flat = args.is_present("flat");
+        let flat = args.is_present("flat") || args.is_present("flat-preset");
+        let generator = match args.value_of("generator") {
+            Some(name) => name,
+            None if flat => "flat",
+            None => DEFAULT_GENERATOR,
+        };
+
+        if flat && generator != "flat" {
+            return Err(format!("--flat and --flat-preset can't be used with the {} generator", generator).into());
+        }
+
+        if generator == "flat" && args.is_present("terrain") {
+            return Err("--terrain can't be used with the flat generator".into());
+        }
 
         if seed == 0 {
             //FIXME random seed ?
         }
 
+        // seed, generator and its settings of an existing world can't be changed
+        let metadata = WorldMetadata::load_or_create(world_path, || {
+            let mut metadata = WorldMetadata::new(seed, generator);
+
+            if let Some(preset) = args.value_of("flat-preset") {
+                metadata.generator_settings.flat = FlatPreset::load(preset)?;
+            }
+
+            if let Some(terrain) = args.value_of("terrain") {
+                metadata.generator_settings.terrain = TerrainSettings::load(terrain)?;
+            }
+
+            // the generator itself is only created with the world
+            if !terrain_generators().iter().any(|name| name == generator) {
+                return Err(format!("unknown terrain generator {} (available: {})", generator, terrain_generators().join(", ")).into());
+            }
+
+            metadata.generator_settings.check(generator)?;
+
+            Ok(metadata)
+        })?;
+
         game::BaseApp::run(
             world_path,
-            seed,
-            flat,
+            metadata,
             view_distance,
             Config::default(),
             layout,
-        );
+        )?;
     } else if let Some(args) = matches.subcommand_matches("render_chunks") {
-        let seed = args.value_of("seed").unwrap_or("0").parse::<isize>()?;
-        biome_generator::generate_biome(seed)?;
+        let seed = args.value_of("seed").unwrap_or("0").parse::<i64>()?;
+        let layers = LayerConfig::load(args.value_of("layers").unwrap_or("default"))?;
+
+        biome_generator::generate_biome(seed, &layers)?;
     } else if let Some(args) = matches.subcommand_matches("dump") {
         dump::dump_map(args)?;
+    } else if let Some(args) = matches.subcommand_matches("dump_layers") {
+        layer_dump::dump_layers(args)?;
     }
 
     Ok(())
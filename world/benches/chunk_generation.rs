@@ -0,0 +1,77 @@
+use std::time::{Duration, Instant};
+
+use world::generator::layers::LayerConfig;
+use world::generator::ChunkGenerator;
+
+const SEED: i64 = 42;
+/** Side of the generated area, in chunks */
+const SIZE: i32 = 32;
+const RUNS: usize = 3;
+
+/**
+ * Best time of a few runs on the whole area, each one starting with empty caches
+ */
+fn measure<G: FnMut(i32, i32)>(create: impl Fn() -> G) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut generate = create();
+            let start = Instant::now();
+
+            for x in 0..SIZE {
+                for z in 0..SIZE {
+                    generate(x, z);
+                }
+            }
+
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, uncached: Duration, cached: Duration) {
+    let chunks = (SIZE * SIZE) as f64;
+
+    println!(
+        "{:<8} uncached {:>8.0} chunks/s, cached {:>8.0} chunks/s, x{:.2}",
+        name,
+        chunks / uncached.as_secs_f64(),
+        chunks / cached.as_secs_f64(),
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
+
+/**
+ * Only the biome requests made by the column provider for each chunk
+ */
+fn biomes(layers: LayerConfig) -> Duration {
+    measure(|| {
+        let (mut unzoomed, mut zoomed) = layers.build(SEED).unwrap().into_generators();
+
+        move |x, z| {
+            let (x, z) = (x as isize, z as isize);
+
+            unzoomed.generate(x * 4 - 2, z * 4 - 2, 10, 10);
+            zoomed.generate(x * 16, z * 16, 16, 16);
+        }
+    })
+}
+
+fn terrain(layers: LayerConfig) -> Duration {
+    measure(|| {
+        let mut generator = ChunkGenerator::with_layers(SEED, layers.build(SEED).unwrap());
+
+        move |x, z| {
+            generator.generate_xz(x, z);
+        }
+    })
+}
+
+fn main() {
+    let layers = LayerConfig::preset("default").unwrap();
+
+    println!("{}x{} chunks, best of {} runs", SIZE, SIZE, RUNS);
+
+    report("biomes", biomes(layers.clone().without_caches()), biomes(layers.clone()));
+    report("terrain", terrain(layers.clone().without_caches()), terrain(layers));
+}
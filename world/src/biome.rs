@@ -5,43 +5,25 @@ use serde::{Deserialize, Serialize};
 use nalgebra::Vector3;
 
 use rand::prelude::*;
-use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use crate::generator::decorators::*;
-use crate::{Block, Chunk, SEA_LEVEL};
+use crate::{BiomeConfig, Block, Chunk, SEA_LEVEL};
 
 lazy_static! {
-    static ref DECORATORS: HashMap<BiomeType, Vec<Box<dyn WorldDecorator + Sync>>> = {
-        let mut map = HashMap::new();
-
-        map.insert(BiomeType::Desert, vec![DecoratorTowerPlant::cactus(30)]);
-        map.insert(BiomeType::DesertHills, vec![DecoratorTowerPlant::cactus(30)]);
-        map.insert(BiomeType::Plain, vec![DecoratorPlantGroup::tallgrass(3)]);
-        map.insert(BiomeType::Hills, vec![DecoratorPlantGroup::tallgrass(3)]);
-        map.insert(BiomeType::Savanna, vec![DecoratorPlantGroup::tallgrass(9), DecoratorTree::fat(20, ForestType::Acacia, false, false)]);
-        map.insert(BiomeType::SavannaPlateau, vec![DecoratorPlantGroup::tallgrass(9), DecoratorTree::fat(20, ForestType::Acacia, false, false)]);
-
-        map.insert(BiomeType::Forest, vec![DecoratorTree::small(10, ForestType::Normal, false, false), DecoratorTree::great(80, ForestType::Normal, false, false), DecoratorTree::fat(20, ForestType::Normal, false, false)]);
-        map.insert(BiomeType::ForestHills, vec![DecoratorTree::small(10, ForestType::Normal, false, false), DecoratorTree::great(80, ForestType::Normal, false, false), DecoratorTree::fat(20, ForestType::Normal, false, false)]);
-        map.insert(BiomeType::Taiga, vec![DecoratorTree::great(10, ForestType::Taiga, false, true), DecoratorTree::very_great(80, ForestType::Taiga, false, true), DecoratorTree::fat(20, ForestType::Taiga, false, true)]);
-        map.insert(BiomeType::TaigaHills, vec![DecoratorTree::great(10, ForestType::Taiga, false, false), DecoratorTree::very_great(80, ForestType::Taiga, false, false), DecoratorTree::fat(20, ForestType::Taiga, false, false)]);
-
-        map.insert(BiomeType::IceForest, vec![DecoratorTree::small(10, ForestType::Normal, false, true), DecoratorTree::great(80, ForestType::Normal, false, true), DecoratorTree::fat(20, ForestType::Normal, false, true)]);
-        map.insert(BiomeType::IceForestHills, vec![DecoratorTree::small(10, ForestType::Normal, false, true), DecoratorTree::great(80, ForestType::Normal, false, true), DecoratorTree::fat(20, ForestType::Normal, false, true)]);
-        map.insert(BiomeType::IceTaiga, vec![DecoratorTree::great(10, ForestType::Taiga, false, true), DecoratorTree::very_great(80, ForestType::Taiga, false, true), DecoratorTree::fat(20, ForestType::Taiga, false, true)]);
-        map.insert(BiomeType::IceTaigaHills, vec![DecoratorTree::great(10, ForestType::Taiga, false, true), DecoratorTree::very_great(80, ForestType::Taiga, false, true), DecoratorTree::fat(20, ForestType::Taiga, false, true)]);
-
-        map.insert(BiomeType::Jungle, vec![DecoratorTree::jungle(150), DecoratorTree::small(10, ForestType::Normal, false, false)]);
-        map.insert(BiomeType::Forest, vec![DecoratorTree::small(20, ForestType::Normal, true, false), DecoratorTree::great(10, ForestType::Normal, true, false), DecoratorTree::fat(5, ForestType::Normal, true, false)]);
-
-        // common decorators
-        for (_, v) in map.iter_mut() {
-            v.push(DecoratorPlantGroup::tallgrass(1));
-            v.push(DecoratorPlantGroup::flowers(1));
-            v.push(DecoratorTree::small(1, ForestType::Classic, false, false));
-        }
+    static ref CUSTOM_BIOMES: Mutex<Option<BiomeConfig>> = Mutex::new(None);
+    static ref CUSTOM_BIOMES_HASH: Mutex<Option<u32>> = Mutex::new(None);
+
+    static ref BIOMES: BiomeRegistry = {
+        let mut custom = CUSTOM_BIOMES.lock().unwrap();
+        BIOMES_USED.store(true, Ordering::SeqCst);
 
-        map
+        match custom.take() {
+            Some(config) => config.build().unwrap(),
+            None => BiomeConfig::from_yaml(include_str!("biome_data.yaml")).and_then(|c| c.build()).unwrap(),
+        }
     };
 
     static ref GRASS_COLORS: [Vector3<f32>; 3] = [
@@ -51,6 +33,85 @@ lazy_static! {
     ];
 }
 
+static BIOMES_USED: AtomicBool = AtomicBool::new(false);
+
+/**
+ * Use the biome definitions of the given YAML file instead of the default ones (`biome_data.yaml`).
+ * Must be called before any biome is used. Chunks only store the ids of their biomes, so a world
+ * can only be opened with the definitions it was created with, see `biomes_hash`
+ */
+pub fn load_biomes(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let yaml = fs::read_to_string(path)?;
+    let config = BiomeConfig::from_yaml(&yaml)?;
+    config.build()?;
+
+    let mut custom = CUSTOM_BIOMES.lock().unwrap();
+
+    if BIOMES_USED.load(Ordering::SeqCst) {
+        return Err("biomes are already loaded".into());
+    }
+
+    *custom = Some(config);
+    *CUSTOM_BIOMES_HASH.lock().unwrap() = Some(crc32fast::hash(yaml.as_bytes()));
+
+    Ok(())
+}
+
+/**
+ * Hash of the biome definitions given to `load_biomes`, None for the default ones
+ */
+pub fn biomes_hash() -> Option<u32> {
+    *CUSTOM_BIOMES_HASH.lock().unwrap()
+}
+
+/**
+ * Grass color of a climate
+ */
+pub fn climate_grass_color(temperature: f32, rainfall: f32) -> Vector3<f32> {
+    let temperature = temperature.clamp(0.0, 1.0);
+    let rainfall = rainfall.clamp(0.0, 1.0) * temperature;
+
+    let color = (temperature - rainfall) * GRASS_COLORS[0]
+                    + (1.0 - temperature) * GRASS_COLORS[1]
+                    + (rainfall) * GRASS_COLORS[2];
+
+    Vector3::new(
+        color.x.clamp(0., 255.),
+        color.y.clamp(0., 255.),
+        color.z.clamp(0., 255.),
+    )
+}
+
+/**
+ * Properties of a biome, built from its definition in the biome data file
+ */
+pub struct BiomeDefinition {
+    pub name: String,
+    pub elevation: f32,
+    pub depth: f32,
+    pub color: (u8, u8, u8),
+    pub temperature: f32,
+    pub rainfall: f32,
+    pub grass_color: Vector3<f32>,
+    pub ocean: bool,
+    pub top_layer: Block,
+    pub top_block: Block,
+    pub column_block: Block,
+    pub sub_column_block: Option<Block>,
+    pub cave_density: f32,
+    pub hills: BiomeType,
+    pub decorators: Vec<Box<dyn WorldDecorator + Sync>>,
+}
+
+/**
+ * Loaded biomes, indexed by id, and climate groups with the biomes they can contain
+ */
+pub struct BiomeRegistry {
+    pub biomes: Vec<BiomeDefinition>,
+    pub groups: Vec<(String, Vec<BiomeType>)>,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
 pub enum BiomeShapeType {
     DeepVeryLow,
     DeepLow,
@@ -102,303 +163,176 @@ impl BiomeShapeType {
     }
 }
 
+macro_rules! builtin_biomes {
+    ($($name:ident = $id:expr,)*) => {
+        /**
+         * Biomes used by the generator, which must be defined by the biome data file, with these ids
+         */
+        #[allow(non_upper_case_globals)]
+        impl BiomeType {
+            $(pub const $name: BiomeType = BiomeType($id);)*
+        }
+
+        pub const BUILTIN_BIOMES: &[&str] = &[$(stringify!($name)),*];
+    }
+}
+
+/**
+ * Id of a biome, whose properties are defined by the loaded biome data file
+ */
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[repr(isize)]
-pub enum BiomeType {
+pub struct BiomeType(u8);
+
+builtin_biomes! {
     Ocean = 0,
-    DeepOcean,
+    DeepOcean = 1,
 
     // classifcal biomes
-    Beach,
-    Plain,
-    Hills,
-    Forest,
-    ForestHills,
-    Swampland,
+    Beach = 2,
+    Plain = 3,
+    Hills = 4,
+    Forest = 5,
+    ForestHills = 6,
+    Swampland = 7,
 
-    Jungle,
-    JungleHills,
-    Moutains,
-    HighMoutains,
+    Jungle = 8,
+    JungleHills = 9,
+    Moutains = 10,
+    HighMoutains = 11,
 
-    Taiga,
-    TaigaHills,
+    Taiga = 12,
+    TaigaHills = 13,
 
     // warms biomes
-    Desert,
-    DesertHills,
-    Savanna,
-    SavannaPlateau,
+    Desert = 14,
+    DesertHills = 15,
+    Savanna = 16,
+    SavannaPlateau = 17,
 
     // ice biomes
-    IceBeach,
-    IcePlain,
-    IceHills,
-    IceForest,
-    IceForestHills,
-    IceMoutains,
-    IceHighMoutains,
-    IceTaiga,
-    IceTaigaHills,
-
-    River,
+    IceBeach = 18,
+    IcePlain = 19,
+    IceHills = 20,
+    IceForest = 21,
+    IceForestHills = 22,
+    IceMoutains = 23,
+    IceHighMoutains = 24,
+    IceTaiga = 25,
+    IceTaigaHills = 26,
+
+    River = 27,
 }
 
 impl BiomeType {
+    /**
+     * Biome of the given id, the ocean if the id isn't one of the loaded biomes
+     */
     pub fn from_id(i: isize) -> BiomeType {
-        unsafe { std::mem::transmute(i) }
+        if i < 0 || i >= BiomeType::count() {
+            return BiomeType::Ocean;
+        }
+
+        BiomeType(i as u8)
     }
 
-    pub fn shape(&self) -> BiomeShapeType {
-        match self {
-            BiomeType::Ocean => BiomeShapeType::DeepMedium,
-            BiomeType::DeepOcean => BiomeShapeType::DeepHigh,
-            BiomeType::Plain => BiomeShapeType::VeryLow,
-            BiomeType::Hills => BiomeShapeType::HillsLow,
-
-            BiomeType::Desert => BiomeShapeType::Low,
-            BiomeType::DesertHills => BiomeShapeType::HillsLow,
-            BiomeType::Savanna => BiomeShapeType::Low,
-            BiomeType::SavannaPlateau => BiomeShapeType::Plateau,
-
-            BiomeType::Jungle => BiomeShapeType::VeryLow,
-            BiomeType::JungleHills => BiomeShapeType::HillsLow,
-            BiomeType::Moutains => BiomeShapeType::HillsMedium,
-            BiomeType::HighMoutains => BiomeShapeType::HillsHigh,
-
-            BiomeType::Taiga => BiomeShapeType::Medium,
-            BiomeType::TaigaHills => BiomeShapeType::HillsLow,
-
-            BiomeType::Beach => BiomeShapeType::Flat,
-            BiomeType::Forest => BiomeShapeType::VeryLow,
-            BiomeType::ForestHills => BiomeShapeType::HillsLow,
-            BiomeType::Swampland => BiomeShapeType::DeepVeryLow,
-            BiomeType::IceBeach => BiomeShapeType::Flat,
-            BiomeType::IcePlain => BiomeShapeType::Medium,
-            BiomeType::IceHills => BiomeShapeType::HillsLow,
-            BiomeType::IceForest => BiomeShapeType::VeryLow,
-            BiomeType::IceForestHills => BiomeShapeType::HillsLow,
-            BiomeType::IceMoutains => BiomeShapeType::HillsMedium,
-            BiomeType::IceHighMoutains => BiomeShapeType::HillsHigh,
-            BiomeType::IceTaiga => BiomeShapeType::Medium,
-            BiomeType::IceTaigaHills => BiomeShapeType::HillsLow,
-
-            BiomeType::River => BiomeShapeType::DeepLow,
-        }
+    /**
+     * Biome of a registry being built, whose id can't be checked against the loaded biomes
+     */
+    pub(crate) fn from_registry_id(i: usize) -> BiomeType {
+        BiomeType(i as u8)
     }
 
-    pub fn color(&self) -> (u8, u8, u8) {
-        match self {
-            BiomeType::Ocean => (0, 119, 190),
-            BiomeType::DeepOcean => (0, 71, 114),
-            BiomeType::Plain => (119, 190, 0),
-
-            BiomeType::Desert => (244, 164, 96),
-            BiomeType::DesertHills => (164, 84, 16),
-            BiomeType::Savanna => (236, 213, 64),
-            BiomeType::SavannaPlateau => (136, 113, 0),
-
-            BiomeType::Jungle => (0, 255, 0),
-            BiomeType::JungleHills => (0, 203, 0),
-            BiomeType::Moutains => (255, 255, 0),
-            BiomeType::HighMoutains => (203, 203, 0),
-
-            BiomeType::Taiga => (0, 255, 255),
-            BiomeType::TaigaHills => (0, 203, 203),
-
-            BiomeType::Beach => (194, 178, 128),
-            BiomeType::Hills => (104, 68, 48),
-            BiomeType::Forest => (138, 138, 138),
-            BiomeType::ForestHills => (78, 78, 78),
-            BiomeType::Swampland => (208, 108, 108),
-            BiomeType::IceBeach => (224, 208, 158),
-            BiomeType::IcePlain => (149, 208, 138),
-            BiomeType::IceHills => (79, 138, 68),
-            BiomeType::IceForest => (208, 208, 208),
-            BiomeType::IceForestHills => (158, 158, 158),
-            BiomeType::IceMoutains => (208, 153, 32),
-            BiomeType::IceHighMoutains => (158, 103, 0),
-            BiomeType::IceTaiga => (38, 143, 192),
-            BiomeType::IceTaigaHills => (0, 73, 132),
-
-            BiomeType::River => (0, 0, 255),
-        }
+    pub fn id(&self) -> isize {
+        self.0 as isize
     }
 
-    pub fn get_hills_version(&self) -> BiomeType {
-        match self {
-            BiomeType::Plain => BiomeType::Hills,
+    pub fn from_name(name: &str) -> Option<BiomeType> {
+        BIOMES.biomes.iter().position(|b| b.name == name).map(|i| BiomeType(i as u8))
+    }
 
-            BiomeType::Desert => BiomeType::DesertHills,
-            BiomeType::Savanna => BiomeType::SavannaPlateau,
+    /**
+     * Number of loaded biomes
+     */
+    pub fn count() -> isize {
+        BIOMES.biomes.len() as isize
+    }
 
-            BiomeType::Jungle => BiomeType::JungleHills,
-            BiomeType::Moutains => BiomeType::HighMoutains,
+    /**
+     * Maximum number of biomes, as chunks store biome ids on one byte
+     */
+    pub fn max_count() -> isize {
+        u8::MAX as isize + 1
+    }
 
-            BiomeType::Taiga => BiomeType::TaigaHills,
+    pub fn definition(&self) -> &'static BiomeDefinition {
+        &BIOMES.biomes[self.0 as usize]
+    }
 
-            BiomeType::Forest => BiomeType::ForestHills,
-            BiomeType::IcePlain => BiomeType::IceHills,
-            BiomeType::IceForest => BiomeType::IceForestHills,
-            BiomeType::IceMoutains => BiomeType::IceHighMoutains,
-            BiomeType::IceTaiga => BiomeType::IceTaigaHills,
+    pub fn name(&self) -> &'static str {
+        &self.definition().name
+    }
 
-            v => *v,
-        }
+    pub fn color(&self) -> (u8, u8, u8) {
+        self.definition().color
+    }
+
+    pub fn get_hills_version(&self) -> BiomeType {
+        self.definition().hills
     }
 
     pub fn temperature(&self) -> f32 {
-        match self {
-            BiomeType::Ocean            => 0.5,
-            BiomeType::DeepOcean        => 0.5,
-            BiomeType::Plain            => 0.8,
-            BiomeType::Hills            => 0.8,
-
-            BiomeType::Desert           => 2.0,
-            BiomeType::DesertHills      => 2.0,
-            BiomeType::Savanna          => 1.2,
-            BiomeType::SavannaPlateau   => 1.0,
-
-            BiomeType::Jungle           => 0.95,
-            BiomeType::JungleHills      => 0.95,
-            BiomeType::Moutains         => 0.2,
-            BiomeType::HighMoutains     => 0.1,
-
-            BiomeType::Taiga            => 0.25,
-            BiomeType::TaigaHills       => 0.25,
-
-            BiomeType::Beach            => 0.8,
-            BiomeType::Forest           => 0.7,
-            BiomeType::ForestHills      => 0.7,
-            BiomeType::Swampland        => 0.8,
-            BiomeType::IceBeach         => 0.05,
-            BiomeType::IcePlain         => 0.05,
-            BiomeType::IceHills         => 0.05,
-            BiomeType::IceForest        => 0.05,
-            BiomeType::IceForestHills   => 0.05,
-            BiomeType::IceMoutains      => -0.5,
-            BiomeType::IceHighMoutains  => -0.6,
-            BiomeType::IceTaiga         => -0.5,
-            BiomeType::IceTaigaHills    => -0.5,
-
-            BiomeType::River            => 0.5,
-        }
+        self.definition().temperature
     }
 
     pub fn rainfall(&self) -> f32 {
-        match self {
-            BiomeType::Ocean            => 0.5,
-            BiomeType::DeepOcean        => 0.5,
-            BiomeType::Plain            => 0.4,
-            BiomeType::Hills            => 0.4,
-
-            BiomeType::Desert           => 0.0,
-            BiomeType::DesertHills      => 0.0,
-            BiomeType::Savanna          => 0.0,
-            BiomeType::SavannaPlateau   => 0.0,
-
-            BiomeType::Jungle           => 0.9,
-            BiomeType::JungleHills      => 0.9,
-            BiomeType::Moutains         => 0.3,
-            BiomeType::HighMoutains     => 0.3,
-
-            BiomeType::Taiga            => 0.8,
-            BiomeType::TaigaHills       => 0.8,
-
-            BiomeType::Beach            => 0.4,
-            BiomeType::Forest           => 0.8,
-            BiomeType::ForestHills      => 0.8,
-            BiomeType::Swampland        => 0.9,
-            BiomeType::IceBeach         => 0.3,
-            BiomeType::IcePlain         => 0.3,
-            BiomeType::IceHills         => 0.3,
-            BiomeType::IceForest        => 0.3,
-            BiomeType::IceForestHills   => 0.3,
-            BiomeType::IceMoutains      => 0.5,
-            BiomeType::IceHighMoutains  => 0.5,
-            BiomeType::IceTaiga         => -0.5,
-            BiomeType::IceTaigaHills    => -0.5,
-
-            BiomeType::River            => 0.5,
-        }
+        self.definition().rainfall
     }
 
     pub fn grass_color(&self) -> Vector3<f32> {
-        match self {
-            BiomeType::Swampland => Vector3::new(106.0, 112.0, 57.0),
-            biome => {
-                let temperature = biome.temperature().clamp(0.0, 1.0);
-                let rainfall = biome.rainfall().clamp(0.0, 1.0) * temperature;
-
-                let color = (temperature - rainfall) * GRASS_COLORS[0]
-                                + (1.0 - temperature) * GRASS_COLORS[1]
-                                + (rainfall) * GRASS_COLORS[2];
-
-                Vector3::new(
-                    color.x.clamp(0., 255.),
-                    color.y.clamp(0., 255.),
-                    color.z.clamp(0., 255.),
-                )
-            }
-        }
+        self.definition().grass_color
     }
 
     pub fn is_ocean(&self) -> bool {
-        match self {
-            BiomeType::Ocean => true,
-            BiomeType::DeepOcean => true,
-            _ => false,
-        }
+        self.definition().ocean
     }
 
     pub fn elevation(&self) -> f32 {
-        self.shape().elevation()
+        self.definition().elevation
     }
 
     pub fn depth(&self) -> f32 {
-        self.shape().depth()
+        self.definition().depth
+    }
+
+    /**
+     * Multiplier of the width of cave tunnels under the biome, 0 disables caves
+     */
+    pub fn cave_density(&self) -> f32 {
+        self.definition().cave_density
     }
 
     pub fn top_layer(&self) -> Block {
-        match self {
-            BiomeType::IceTaiga
-            | BiomeType::IceTaigaHills
-            | BiomeType::IceHighMoutains
-            | BiomeType::IceMoutains
-            | BiomeType::IceForest
-            | BiomeType::IceHills
-            | BiomeType::IcePlain
-            | BiomeType::IceBeach => Block::Snow,
-            _ => Block::Air,
-        }
+        self.definition().top_layer
     }
 
     pub fn top_block(&self) -> Block {
-        match self {
-            BiomeType::Desert | BiomeType::DesertHills => Block::Sand,
-            BiomeType::Beach | BiomeType::IceBeach => Block::Sand,
-            _ => Block::Grass,
-        }
+        self.definition().top_block
     }
 
     pub fn column_block(&self) -> Block {
-        match self {
-            BiomeType::Desert | BiomeType::DesertHills => Block::Sand,
-            BiomeType::Beach | BiomeType::IceBeach => Block::Sand,
-            _ => Block::Dirt,
-        }
+        self.definition().column_block
     }
 
     pub fn sub_column_block(&self) -> Option<Block> {
-        None
+        self.definition().sub_column_block
     }
 
-    pub fn decorators(&self) -> Option<&'static Vec<Box<dyn WorldDecorator + Sync>>> {
-        DECORATORS.get(self)
+    pub fn decorators(&self) -> &'static [Box<dyn WorldDecorator + Sync>] {
+        &self.definition().decorators
     }
 
-    pub fn generate_column(&self, chunk: &mut Chunk, x: i32, z: i32) {
-        let top_column_height: i32 = (3. + rand::thread_rng().gen::<f32>() * 0.25) as i32;
+    pub fn generate_column(&self, chunk: &mut Chunk, x: i32, z: i32, random: &mut StdRng) {
+        let top_column_height: i32 = (3. + random.gen::<f32>() * 0.25) as i32;
 
         let mut top = -1;
         let mut first = true;
@@ -441,7 +375,7 @@ impl BiomeType {
                 }
 
                 if let Some(b) = self.sub_column_block() {
-                    top = rand::thread_rng().gen_range(0, 4) + (y - SEA_LEVEL).max(0);
+                    top = random.gen_range(0, 4) + (y - SEA_LEVEL).max(0);
                     column_type = b;
                 }
             }
@@ -449,53 +383,26 @@ impl BiomeType {
     }
 }
 
-#[repr(isize)]
-pub enum BiomeGroup {
-    Warm = 0,
-    Temperate,
-    Cold,
-    Iced,
-}
+/**
+ * Climate group of biomes, as defined in the biome data file
+ */
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BiomeGroup(isize);
 
 impl BiomeGroup {
     pub fn count() -> isize {
-        4
+        BIOMES.groups.len() as isize
     }
 
     pub fn get(i: isize) -> BiomeGroup {
-        unsafe { std::mem::transmute(i) }
+        BiomeGroup(i)
     }
 
-    pub fn biomes(&self) -> Vec<BiomeType> {
-        //FIXME roofed & birch forests ?
-        match self {
-            BiomeGroup::Warm => vec![
-                BiomeType::Desert,
-                BiomeType::Desert,
-                BiomeType::Savanna,
-                BiomeType::Plain,
-            ],
-            BiomeGroup::Temperate => vec![
-                BiomeType::Jungle,
-                BiomeType::Forest,
-                BiomeType::Forest,
-                BiomeType::Moutains,
-                BiomeType::Plain,
-                BiomeType::Forest,
-                BiomeType::Swampland,
-            ],
-            BiomeGroup::Cold => vec![
-                BiomeType::Forest,
-                BiomeType::Moutains,
-                BiomeType::Taiga,
-                BiomeType::Plain,
-            ],
-            BiomeGroup::Iced => vec![
-                BiomeType::IcePlain,
-                BiomeType::IceForest,
-                BiomeType::IceTaiga,
-                BiomeType::IceMoutains,
-            ],
-        }
+    pub fn name(&self) -> &'static str {
+        &BIOMES.groups[self.0 as usize].0
+    }
+
+    pub fn biomes(&self) -> &'static [BiomeType] {
+        &BIOMES.groups[self.0 as usize].1
     }
 }
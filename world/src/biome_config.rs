@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use crate::*;
+use crate::generator::decorators::*;
+use serde_derive::*;
+
+use nalgebra::Vector3;
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeSize {
+    Small,
+    Great,
+    VeryGreat,
+    Fat,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VeinBlock {
+    Coal,
+    Iron,
+    Gold,
+    Granite,
+    Andesite,
+    Diorite,
+    Gravel,
+    Clay,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DecoratorConfig {
+    Tallgrass { count: usize },
+    Flowers { count: usize },
+    Cactus { count: usize },
+    JungleTree { count: usize },
+    Tree {
+        size: TreeSize,
+        forest: ForestType,
+        count: usize,
+        water: Option<bool>,
+        snow: Option<bool>,
+    },
+    Vein { block: VeinBlock, count: usize },
+    /** Decorators of a set of `decorator_sets` */
+    Set { name: String },
+}
+
+impl DecoratorConfig {
+    fn build(&self) -> Box<dyn WorldDecorator + Sync> {
+        match self {
+            DecoratorConfig::Tallgrass { count } => DecoratorPlantGroup::tallgrass(*count),
+            DecoratorConfig::Flowers { count } => DecoratorPlantGroup::flowers(*count),
+            DecoratorConfig::Cactus { count } => DecoratorTowerPlant::cactus(*count),
+            DecoratorConfig::JungleTree { count } => DecoratorTree::jungle(*count),
+            DecoratorConfig::Tree { size, forest, count, water, snow } => {
+                let (water, snow) = (water.unwrap_or(false), snow.unwrap_or(false));
+
+                match size {
+                    TreeSize::Small => DecoratorTree::small(*count, *forest, water, snow),
+                    TreeSize::Great => DecoratorTree::great(*count, *forest, water, snow),
+                    TreeSize::VeryGreat => DecoratorTree::very_great(*count, *forest, water, snow),
+                    TreeSize::Fat => DecoratorTree::fat(*count, *forest, water, snow),
+                }
+            }
+            DecoratorConfig::Vein { block, count } => match block {
+                VeinBlock::Coal => DecoratorVein::coal(*count),
+                VeinBlock::Iron => DecoratorVein::iron(*count),
+                VeinBlock::Gold => DecoratorVein::gold(*count),
+                VeinBlock::Granite => DecoratorVein::granite(*count),
+                VeinBlock::Andesite => DecoratorVein::andesite(*count),
+                VeinBlock::Diorite => DecoratorVein::diorite(*count),
+                VeinBlock::Gravel => DecoratorVein::gravel(*count),
+                VeinBlock::Clay => DecoratorVein::clay(*count),
+            },
+            DecoratorConfig::Set { .. } => unreachable!("decorator sets are expanded when loaded"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BiomeDefinitionConfig {
+    name: String,
+
+    shape: BiomeShapeType,
+    elevation: Option<f32>,
+    depth: Option<f32>,
+
+    color: (u8, u8, u8),
+    temperature: f32,
+    rainfall: f32,
+    grass_color: Option<(f32, f32, f32)>,
+
+    ocean: Option<bool>,
+    top_layer: Option<Block>,
+    top_block: Option<Block>,
+    column_block: Option<Block>,
+    sub_column_block: Option<Block>,
+    cave_density: Option<f32>,
+
+    hills: Option<String>,
+    decorators: Option<Vec<DecoratorConfig>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BiomeGroupConfig {
+    name: String,
+    biomes: Vec<String>,
+}
+
+/**
+ * Biome definitions, as stored in `biome_data.yaml`
+ */
+#[derive(Clone, Debug, Deserialize)]
+pub struct BiomeConfig {
+    decorator_sets: Option<HashMap<String, Vec<DecoratorConfig>>>,
+    groups: Vec<BiomeGroupConfig>,
+    biomes: Vec<BiomeDefinitionConfig>,
+}
+
+impl BiomeConfig {
+    pub fn from_yaml(yaml: &str) -> Result<BiomeConfig, Box<dyn std::error::Error>> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    fn biome(&self, name: &str) -> Result<BiomeType, Box<dyn std::error::Error>> {
+        match self.biomes.iter().position(|b| b.name == name) {
+            Some(i) => Ok(BiomeType::from_registry_id(i)),
+            None => Err(format!("unknown biome {}", name).into()),
+        }
+    }
+
+    fn decorators(&self, biome: &BiomeDefinitionConfig) -> Result<Vec<Box<dyn WorldDecorator + Sync>>, Box<dyn std::error::Error>> {
+        let mut decorators = Vec::new();
+
+        for decorator in biome.decorators.iter().flatten() {
+            match decorator {
+                DecoratorConfig::Set { name } => {
+                    let set = self
+                        .decorator_sets
+                        .as_ref()
+                        .and_then(|sets| sets.get(name))
+                        .ok_or_else(|| format!("biome {}: unknown decorator set {}", biome.name, name))?;
+
+                    for decorator in set {
+                        if let DecoratorConfig::Set { .. } = decorator {
+                            return Err(format!("decorator set {} contains another set", name).into());
+                        }
+
+                        decorators.push(decorator.build());
+                    }
+                }
+                decorator => decorators.push(decorator.build()),
+            }
+        }
+
+        Ok(decorators)
+    }
+
+    /**
+     * Check the definitions and build the decorators of the biomes
+     */
+    pub fn build(&self) -> Result<BiomeRegistry, Box<dyn std::error::Error>> {
+        if self.biomes.len() > BiomeType::max_count() as usize {
+            return Err(format!("at most {} biomes can be defined", BiomeType::max_count()).into());
+        }
+
+        for (i, name) in BUILTIN_BIOMES.iter().enumerate() {
+            if self.biomes.get(i).map(|b| b.name.as_str()) != Some(*name) {
+                return Err(format!("biome {} must be defined at position {}", name, i).into());
+            }
+        }
+
+        if self.groups.is_empty() {
+            return Err("at least one biome group must be defined".into());
+        }
+
+        let mut biomes = Vec::with_capacity(self.biomes.len());
+
+        for (i, biome) in self.biomes.iter().enumerate() {
+            if self.biomes[..i].iter().any(|b| b.name == biome.name) {
+                return Err(format!("biome {} is defined twice", biome.name).into());
+            }
+
+            let temperature = biome.temperature;
+            let rainfall = biome.rainfall;
+
+            biomes.push(BiomeDefinition {
+                name: biome.name.clone(),
+                elevation: biome.elevation.unwrap_or_else(|| biome.shape.elevation()),
+                depth: biome.depth.unwrap_or_else(|| biome.shape.depth()),
+                color: biome.color,
+                temperature,
+                rainfall,
+                grass_color: match biome.grass_color {
+                    Some((r, g, b)) => Vector3::new(r, g, b),
+                    None => climate_grass_color(temperature, rainfall),
+                },
+                ocean: biome.ocean.unwrap_or(false),
+                top_layer: biome.top_layer.unwrap_or(Block::Air),
+                top_block: biome.top_block.unwrap_or(Block::Grass),
+                column_block: biome.column_block.unwrap_or(Block::Dirt),
+                sub_column_block: biome.sub_column_block,
+                cave_density: biome.cave_density.unwrap_or(1.0),
+                hills: match &biome.hills {
+                    Some(name) => self.biome(name)?,
+                    None => BiomeType::from_registry_id(i),
+                },
+                decorators: self.decorators(biome)?,
+            });
+        }
+
+        let mut groups = Vec::with_capacity(self.groups.len());
+
+        for group in &self.groups {
+            if group.biomes.is_empty() {
+                return Err(format!("biome group {} is empty", group.name).into());
+            }
+
+            let biomes = group.biomes.iter().map(|name| self.biome(name)).collect::<Result<_, _>>()?;
+            groups.push((group.name.clone(), biomes));
+        }
+
+        Ok(BiomeRegistry { biomes, groups })
+    }
+}
@@ -129,6 +129,15 @@ pub enum Block {
     GlassYellow,
 
     Mirror,
+
+    // underground, new blocks must be added at the end as ids are stored in chunks
+    CoalOre,
+    IronOre,
+    GoldOre,
+    Granite,
+    Andesite,
+    Diorite,
+    Clay,
 }
 
 impl std::fmt::Display for Block {
@@ -138,6 +147,13 @@ impl std::fmt::Display for Block {
 }
 
 impl Block {
+    /**
+     * Number of block types, ids are in 0..count()
+     */
+    pub fn count() -> u32 {
+        Block::Clay as u32 + 1
+    }
+
     pub fn from_id(i: u32) -> Block {
         unsafe { std::mem::transmute(i) }
     }
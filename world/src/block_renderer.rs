@@ -1,4 +1,4 @@
-use crate::{World, ChunkMesh, BlockFace, Block};
+use crate::{ChunkSnapshot, ChunkMesh, BlockFace, Block};
 use nalgebra::{Vector2, Vector3};
 
 #[derive(Clone, Copy)]
@@ -77,14 +77,14 @@ impl BlockRenderer {
         );
     }
 
-    pub fn render(&self, world: &World, self_type: Block, position: Vector3<i32>, mesh: &mut ChunkMesh) {
+    pub fn render(&self, chunks: &ChunkSnapshot, self_type: Block, position: Vector3<i32>, mesh: &mut ChunkMesh) {
         match self {
             BlockRenderer::Empty => (),
             BlockRenderer::ClassicBlock{faces, height, width, continuum} => {
                 let mut height = *height;
 
                 if *continuum {
-                    if let Some(block) = world.block_at(position + BlockFace::Up.relative()) {
+                    if let Some(block) = chunks.block_at(position + BlockFace::Up.relative()) {
                         if block == self_type {
                             height = 10;
                         }
@@ -96,7 +96,7 @@ impl BlockRenderer {
                     let rel = face.relative();
 
                     // skip face if the neighbouring block is opaque (the face won't be seen)
-                    if let Some(block) = world.block_at(position + rel) {
+                    if let Some(block) = chunks.block_at(position + rel) {
                         if block.is_opaque() && height == 10 {
                             continue;
                         }
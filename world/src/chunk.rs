@@ -1,16 +1,55 @@
 extern crate serde;
 
-use std::{cmp::Ordering, fs::File, io::prelude::*, path::Path, rc::Rc};
+use std::{cmp::Ordering, fs::File, io, io::prelude::*, path::Path, sync::Arc};
 
 use nalgebra::{Vector2, Vector3, Vector4};
 use serde::{Deserialize, Serialize, Serializer};
 
-use crate::generator::decorators::decorator_random;
-use crate::{main_world, BiomeType, Block, World, ChunkMesh, MAX_HEIGHT, SEA_LEVEL};
+use crate::{write_atomic, BiomeType, Block, ChunkSection, MAX_HEIGHT, SEA_LEVEL, SECTION_SIZE};
 
 const WIDTH: i32 = 16;
 const HEIGHT: i32 = MAX_HEIGHT;
 const COUNT: i32 = WIDTH * WIDTH * HEIGHT;
+const SECTIONS: i32 = HEIGHT / SECTION_SIZE;
+
+/**
+ * Chunk file format:
+ *  - header: magic (4 bytes), version (u16), reserved (u16), payload size (u32), payload crc32 (u32)
+ *  - payload (v1): coords (2 * i32), decorated (u8), blocks (COUNT * u16),
+ *    biomes (WIDTH * WIDTH * u8), grass colors (WIDTH * WIDTH * 4 * f32)
+ *
+ * All values are little endian. Files without the magic are legacy raw dumps (one byte per block).
+ */
+const FORMAT_MAGIC: &[u8; 4] = b"GLCK";
+const FORMAT_VERSION: u16 = 1;
+const FORMAT_HEADER_SIZE: usize = 16;
+
+fn invalid_data(message: &str) -> Box<dyn std::error::Error> {
+    Box::new(io::Error::new(io::ErrorKind::InvalidData, message.to_string()))
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8], count: usize) -> Result<&'a [u8], Box<dyn std::error::Error>> {
+    if data.len() < count {
+        return Err(invalid_data("truncated chunk data"));
+    }
+
+    let (result, rest) = data.split_at(count);
+    *data = rest;
+
+    Ok(result)
+}
+
+fn read_u16(data: &mut &[u8]) -> Result<u16, Box<dyn std::error::Error>> {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(read_bytes(data, 2)?);
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(data: &mut &[u8]) -> Result<u32, Box<dyn std::error::Error>> {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(read_bytes(data, 4)?);
+    Ok(u32::from_le_bytes(bytes))
+}
 
 pub fn serialize_array<S, T>(array: &[T], serializer: S) -> Result<S::Ok, S::Error>
 where S: Serializer, T: Serialize {
@@ -46,15 +85,16 @@ serde_array!(block_arr, COUNT as usize);
 serde_array!(color_arr, (WIDTH * WIDTH * 3) as usize);
 serde_array!(biome_arr, (WIDTH * WIDTH) as usize);
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Chunk {
     coords: Vector2<i32>,
-    pub blocks: Vec<Block>,
+    sections: Vec<Option<ChunkSection>>,
     pub grass_color: Vec<Vector4<f32>>,
     biomes: Vec<BiomeType>,
 
     decorated: bool,
     modified: bool,
+    unsaved: bool,
 }
 
 impl Ord for Chunk {
@@ -78,53 +118,194 @@ impl PartialEq for Chunk {
 impl Eq for Chunk {}
 
 impl Chunk {
-    pub fn new_empty(x: i32, z: i32) -> Rc<Chunk> {
-        Rc::new(Chunk {
+    pub fn new(x: i32, z: i32) -> Chunk {
+        Chunk {
             coords: Vector2::new(x, z),
-            blocks: vec![Block::Air; COUNT as usize],
+            sections: vec![None; SECTIONS as usize],
             decorated: false,
             grass_color: vec![Vector4::zeros(); (WIDTH * WIDTH) as usize],
             biomes: vec![BiomeType::Ocean; WIDTH as usize * WIDTH as usize],
             modified: true,
-        })
+            unsaved: false,
+        }
+    }
+
+    pub fn new_empty(x: i32, z: i32) -> Arc<Chunk> {
+        Arc::new(Chunk::new(x, z))
     }
 
-    pub fn new_from_file(x: i32, z: i32, path: &Path) -> Result<Rc<Chunk>, Box<dyn std::error::Error>> {
+    pub fn new_from_file(x: i32, z: i32, path: &Path) -> Result<Chunk, Box<dyn std::error::Error>> {
         let mut file = File::open(path)?;
-        let mut result = Vec::new();
-        let mut blocks = Vec::new();
+        let mut data = Vec::new();
+
+        file.read_to_end(&mut data)?;
+
+        Chunk::from_bytes(x, z, &data)
+    }
+
+    pub fn from_bytes(x: i32, z: i32, data: &[u8]) -> Result<Chunk, Box<dyn std::error::Error>> {
+        if data.len() < FORMAT_HEADER_SIZE || &data[0..4] != FORMAT_MAGIC {
+            return Chunk::from_raw_bytes(x, z, data);
+        }
+
+        let mut header = &data[4..FORMAT_HEADER_SIZE];
+
+        let version = read_u16(&mut header)?;
+        let _reserved = read_u16(&mut header)?;
+        let size = read_u32(&mut header)? as usize;
+        let checksum = read_u32(&mut header)?;
+
+        if version == 0 || version > FORMAT_VERSION {
+            return Err(invalid_data(&format!("unsupported chunk format version {}", version)));
+        }
+
+        let payload = &data[FORMAT_HEADER_SIZE..];
+
+        if payload.len() != size {
+            return Err(invalid_data("chunk payload size mismatch"));
+        }
+
+        if crc32fast::hash(payload) != checksum {
+            return Err(invalid_data("chunk checksum mismatch"));
+        }
+
+        let mut payload = payload;
+        let data = &mut payload;
+
+        let coords = Vector2::new(read_u32(data)? as i32, read_u32(data)? as i32);
+
+        if coords != Vector2::new(x, z) {
+            return Err(invalid_data("chunk coordinates mismatch"));
+        }
+
+        let decorated = read_bytes(data, 1)?[0] != 0;
+
+        let mut blocks = Vec::with_capacity(COUNT as usize);
+
+        for _ in 0..COUNT {
+            let id = read_u16(data)? as u32;
+
+            if id >= Block::count() {
+                return Err(invalid_data("unknown block id"));
+            }
+
+            blocks.push(Block::from_id(id));
+        }
+
+        let mut biomes = Vec::with_capacity((WIDTH * WIDTH) as usize);
+
+        // ids of biomes that aren't loaded anymore fall back to the ocean, the blocks are still valid
+        for &id in read_bytes(data, (WIDTH * WIDTH) as usize)? {
+            biomes.push(BiomeType::from_id(id as isize));
+        }
+
+        let mut grass_color = Vec::with_capacity((WIDTH * WIDTH) as usize);
+
+        for _ in 0..WIDTH * WIDTH {
+            let mut color = Vector4::zeros();
+
+            for i in 0..4 {
+                color[i] = f32::from_bits(read_u32(data)?);
+            }
+
+            grass_color.push(color);
+        }
+
+        if !data.is_empty() {
+            return Err(invalid_data("trailing chunk data"));
+        }
+
+        Ok(Chunk {
+            coords,
+            sections: sections_from_blocks(&blocks),
+            decorated,
+            grass_color,
+            biomes,
+            modified: true,
+            unsaved: false,
+        })
+    }
+
+    /**
+     * Read a legacy chunk, dumped by `dump_chunk_raw`
+     */
+    fn from_raw_bytes(x: i32, z: i32, data: &[u8]) -> Result<Chunk, Box<dyn std::error::Error>> {
+        if data.len() != COUNT as usize {
+            return Err(invalid_data("invalid raw chunk size"));
+        }
 
-        file.read_to_end(&mut result)?;
+        let mut blocks = Vec::with_capacity(COUNT as usize);
+
+        for &v in data {
+            if v as u32 > Block::Mirror as u32 {
+                return Err(invalid_data("unknown block id"));
+            }
 
-        for v in result {
             blocks.push(Block::from_id(v as u32));
         }
 
-        Ok(Rc::new(Chunk {
+        Ok(Chunk {
             coords: Vector2::new(x, z),
-            blocks,
+            sections: sections_from_blocks(&blocks),
             decorated: true,
             grass_color: vec![Vector4::zeros(); (WIDTH * WIDTH) as usize],
             biomes: vec![BiomeType::Ocean; WIDTH as usize * WIDTH as usize],
             modified: true,
-        }))
+            unsaved: false,
+        })
     }
 
-    pub fn new_example_chunk(x: i32, z: i32) -> Rc<Chunk> {
-        let mut chunk = Chunk::new_empty(x, z);
-        let mut_chunk = unsafe { Rc::get_mut_unchecked(&mut chunk) };
+    /**
+     * Serialize the chunk in the versioned chunk format.
+     * `modified` and `unsaved` are runtime states and are not stored
+     */
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(9 + COUNT as usize * 2 + (WIDTH * WIDTH) as usize * 17);
+
+        payload.extend_from_slice(&self.coords.x.to_le_bytes());
+        payload.extend_from_slice(&self.coords.y.to_le_bytes());
+        payload.push(self.decorated as u8);
+
+        for block in self.flat_blocks() {
+            payload.extend_from_slice(&(block as u16).to_le_bytes());
+        }
+
+        for biome in &self.biomes {
+            payload.push(biome.id() as u8);
+        }
+
+        for color in &self.grass_color {
+            for i in 0..4 {
+                payload.extend_from_slice(&color[i].to_bits().to_le_bytes());
+            }
+        }
 
-        mut_chunk.decorated = true;
+        let mut result = Vec::with_capacity(FORMAT_HEADER_SIZE + payload.len());
+
+        result.extend_from_slice(FORMAT_MAGIC);
+        result.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        result.extend_from_slice(&0u16.to_le_bytes());
+        result.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        result.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        result.extend_from_slice(&payload);
+
+        result
+    }
+
+    pub fn new_example_chunk(x: i32, z: i32) -> Arc<Chunk> {
+        let mut chunk = Chunk::new(x, z);
+
+        chunk.decorated = true;
 
         for x in 0..WIDTH {
             for z in 0..WIDTH {
                 for y in 0..SEA_LEVEL {
-                    mut_chunk.set_block_at_chunk(x, y, z, Block::Grass);
+                    chunk.set_block_at_chunk(x, y, z, Block::Grass);
                 }
             }
         }
 
-        chunk
+        Arc::new(chunk)
     }
 
     pub fn biome_at(&self, x: i32, z: i32) -> &BiomeType {
@@ -135,6 +316,10 @@ impl Chunk {
         &mut self.biomes[(x + z * WIDTH) as usize]
     }
 
+    pub fn modified(&self) -> bool {
+        self.modified
+    }
+
     pub fn check_modified(&mut self) -> bool {
         if self.modified {
             self.modified = false;
@@ -168,21 +353,49 @@ impl Chunk {
         self.modified = true;
     }
 
+    /**
+     * Whether the chunk content differs from what is stored on disk
+     */
+    pub fn unsaved(&self) -> bool {
+        self.unsaved
+    }
+
     pub fn block_at_chunk(&self, x: i32, y: i32, z: i32) -> Block {
-        if y < 0 || y >= MAX_HEIGHT {
-            Block::Air
-        } else {
-            self.blocks[(x + z * WIDTH + y * WIDTH * WIDTH) as usize]
+        if !(0..MAX_HEIGHT).contains(&y) {
+            return Block::Air;
+        }
+
+        match &self.sections[(y / SECTION_SIZE) as usize] {
+            Some(section) => section.block_at(x, y % SECTION_SIZE, z),
+            None => Block::Air,
         }
     }
 
     pub fn set_block_at_chunk(&mut self, x: i32, y: i32, z: i32, block: Block) {
-        if y < 0 || y > MAX_HEIGHT {
+        if !(0..MAX_HEIGHT).contains(&y) {
             return;
         }
 
+        let section = &mut self.sections[(y / SECTION_SIZE) as usize];
+
+        if section.is_none() {
+            if block == Block::Air {
+                return;
+            }
+
+            *section = Some(ChunkSection::new());
+        }
+
+        if let Some(s) = section {
+            s.set_block_at(x, y % SECTION_SIZE, z, block);
+
+            if s.is_empty() {
+                *section = None;
+            }
+        }
+
         self.modified = true;
-        self.blocks[(x + z * WIDTH + y * WIDTH * WIDTH) as usize] = block
+        self.unsaved = true;
     }
 
     pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: Block) {
@@ -212,64 +425,70 @@ impl Chunk {
     pub fn chunk_filled_metadata(&self) -> [bool; 16] {
         let mut result = [false; 16];
 
-        for vy in 0..16 {
-            let base_y = vy * 16;
-
-            'l: for y in 0..16 {
-                for z in 0..16 {
-                    for x in 0..16 {
-                        if self.block_at_chunk(x, base_y + y, z) != Block::Air {
-                            result[vy as usize] = true;
-                            break 'l;
-                        }
-                    }
-                }
-            }
+        for (i, section) in self.sections.iter().enumerate() {
+            result[i] = section.is_some();
         }
 
         result
     }
 
+    /**
+     * Iterate over blocks in the flat order (x, then z, then y)
+     */
+    fn flat_blocks(&self) -> impl Iterator<Item=Block> + '_ {
+        (0..COUNT).map(move |i| self.block_at_chunk(i % WIDTH, i / (WIDTH * WIDTH), (i / WIDTH) % WIDTH))
+    }
+
     pub fn decorated(&self) -> bool {
         self.decorated
     }
 
-    pub fn decorate(&mut self) {
-        if self.decorated {
-            return;
-        }
-
+    /**
+     * Mark the chunk as finalized: the decorations of its neighbourhood are applied (see `ChunkPipeline`)
+     */
+    pub fn set_decorated(&mut self) {
         self.decorated = true;
+    }
 
-        let biome = self.biome_at(0, 0);
-        let world = main_world();
-        let mut random = decorator_random(world, self.coords());
+    pub fn set_saved(&mut self) {
+        self.unsaved = false;
+    }
 
-        for decorator in biome.decorators().unwrap_or(&vec![]) {
-            let p = self.position();
-            decorator.decorate(world, &mut random, Vector3::new(p.x, 0, p.y));
-        }
+    /**
+     * Write the chunk on its next save, even if it wasn't modified (e.g. its stored copy was lost)
+     */
+    pub fn set_unsaved(&mut self) {
+        self.unsaved = true;
     }
 
     pub fn dump_chunk_raw(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
-        let mut file = File::create(path.join(&Path::new(&format!(
-            "{}_{}.ck",
-            self.coords.x, self.coords.y
-        ))))?;
         let mut blocks: [u8; COUNT as usize] = [0; COUNT as usize];
 
-        for i in 0..COUNT as usize {
-            blocks[i] = (self.blocks[i] as isize) as u8;
+        for (i, block) in self.flat_blocks().enumerate() {
+            blocks[i] = (block as isize) as u8;
         }
 
-        file.write_all(&blocks)?;
-
-        Ok(())
+        write_atomic(&path.join(format!("{}_{}.ck", self.coords.x, self.coords.y)), &blocks)
     }
+}
+
+fn sections_from_blocks(blocks: &[Block]) -> Vec<Option<ChunkSection>> {
+    let mut sections = vec![None; SECTIONS as usize];
+
+    for (i, block) in blocks.iter().enumerate() {
+        if *block == Block::Air {
+            continue;
+        }
+
+        let i = i as i32;
+        let y = i / (WIDTH * WIDTH);
 
-    pub fn mesh(&self, world: &World) -> ChunkMesh {
-        ChunkMesh::from_chunk(world, self)
+        sections[(y / SECTION_SIZE) as usize]
+            .get_or_insert_with(ChunkSection::new)
+            .set_block_at(i % WIDTH, y % SECTION_SIZE, (i / WIDTH) % WIDTH, *block);
     }
+
+    sections
 }
 
 pub fn world_to_chunk(position: Vector3<i32>) -> (i32, i32) {
@@ -1,80 +1,455 @@
 extern crate serde_json;
 
-use crate::{generator::ChunkGenerator, main_world, Chunk};
+use crate::{generator::ChunkPipeline, region_coords, write_atomic, Chunk, ChunkBatch, ChunkQueue, RegionFile, World};
 
-use std::fs::create_dir_all;
+use std::fs::{create_dir_all, read_dir, remove_file, rename};
 
-use std::{rc::Rc, sync::mpsc};
+use std::{collections::{HashMap, HashSet}, fmt, sync::{mpsc, Arc, RwLock, Weak}};
 use std::path::{Path, PathBuf};
 
+const QUARANTINE_FOLDER: &str = "quarantine";
+
+/** Coordinates and path of a chunk file of older worlds */
+type LegacyChunkFile = (i32, i32, PathBuf);
+
+/**
+ * Failure of the chunk manager, reported to the game through `World::errors`
+ */
+#[derive(Debug)]
+pub enum ChunkError {
+    /**
+     * Stored chunk data couldn't be read: it was moved to the quarantine folder
+     * (if possible) and the chunk is generated again
+     */
+    Corrupted {
+        x: i32,
+        z: i32,
+        quarantine: Option<PathBuf>,
+        message: String,
+    },
+    /** A chunk couldn't be written to the world folder */
+    Save { x: i32, z: i32, message: String },
+    /** The world folder itself couldn't be used */
+    Storage(String),
+    /** Missing chunks couldn't be generated, they are requested again when seen */
+    Generation(String),
+}
+
+impl fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChunkError::Corrupted { x, z, quarantine: Some(path), message } => write!(
+                f, "chunk ({}, {}) is corrupted ({}), moved to {} and regenerated", x, z, message, path.display()
+            ),
+            ChunkError::Corrupted { x, z, quarantine: None, message } => write!(
+                f, "chunk ({}, {}) is corrupted ({}) and can't be quarantined, regenerated", x, z, message
+            ),
+            ChunkError::Save { x, z, message } => write!(f, "can't save chunk ({}, {}): {}", x, z, message),
+            ChunkError::Storage(message) => write!(f, "world storage error: {}", message),
+            ChunkError::Generation(message) => write!(f, "can't generate chunks: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ChunkError {}
+
 pub struct ChunkManager {
-    generator: ChunkGenerator,
     path: PathBuf,
-    flat: bool,
+    pipeline: ChunkPipeline,
+    pool: rayon::ThreadPool,
+    errors: mpsc::Sender<ChunkError>,
 }
 
 impl ChunkManager {
-    pub fn new(world_path: &str, seed: isize, flat: bool, channel: mpsc::Receiver<(bool, i32, i32)>) {
+    pub fn new(
+        world: Weak<RwLock<World>>,
+        world_path: &str,
+        pipeline: ChunkPipeline,
+        queue: Arc<ChunkQueue>,
+        errors: mpsc::Sender<ChunkError>,
+    ) {
         let mut manager = ChunkManager {
-            generator: ChunkGenerator::new(seed),
             path: Path::new(world_path).to_path_buf(),
-            flat,
+            pipeline,
+            pool: rayon::ThreadPoolBuilder::new()
+                .thread_name(|i| format!("chunk-generator-{}", i))
+                .build()
+                .unwrap(),
+            errors,
         };
 
-        create_dir_all(&manager.path).unwrap();
+        if let Err(e) = create_dir_all(&manager.path) {
+            manager.report(ChunkError::Storage(e.to_string()));
+        } else if let Err(e) = manager.migrate_legacy_chunks() {
+            manager.report(ChunkError::Storage(e.to_string()));
+        }
 
-        while let Ok((is_load, x, z)) = channel.recv() {
-            if is_load {
-                manager.load_or_generate_chunk_and_neighborhood(x, z).unwrap()
-            } else {
-                manager.unload_chunk(x, z).unwrap()
+        while let Some(batch) = queue.pop() {
+            match world.upgrade() {
+                Some(world) => manager.process(&world, batch),
+                // the last chunks of the world are given with the batch
+                None => manager.finish(batch),
             }
         }
     }
 
+    fn process(&mut self, world: &RwLock<World>, batch: ChunkBatch) {
+        self.save_chunks(&batch.saves.iter().map(|c| &**c).collect::<Vec<_>>());
+        self.unload_chunks(world, &batch.unloads);
+
+        // loads of a batch are generated in parallel
+        self.load_chunks(world, &batch.loads);
+
+        if !batch.flushes.is_empty() {
+            self.save_all(world);
+        }
+
+        for ack in batch.flushes {
+            // the requester may have given up waiting
+            let _ = ack.send(());
+        }
+    }
+
+    /**
+     * Save the chunks of a dropped world, its loads and unloads are dropped
+     */
+    fn finish(&mut self, batch: ChunkBatch) {
+        self.save_chunks(&batch.saves.iter().map(|c| &**c).collect::<Vec<_>>());
+
+        for ack in batch.flushes {
+            let _ = ack.send(());
+        }
+    }
+
+    fn report(&self, error: ChunkError) {
+        // the world may already be dropped
+        let _ = self.errors.send(error);
+    }
+
+    /**
+     * Region file containing the given chunk
+     */
     pub fn chunk_file(&self, x: i32, z: i32) -> PathBuf {
-        self.path.join(format!("{}_{}.ck", x, z))
+        let (rx, rz) = region_coords(x, z);
+
+        self.path.join(format!("r.{}.{}.rg", rx, rz))
     }
 
-    pub fn unload_chunk(&self, x: i32, z: i32) -> Result<(), Box<dyn std::error::Error>> {
-        main_world().remove_chunk(x, z);
+    /**
+     * Convert the chunk files of older worlds (one file per chunk) to region files
+     */
+    pub fn migrate_legacy_chunks(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut regions: HashMap<(i32, i32), Vec<LegacyChunkFile>> = HashMap::new();
+
+        for entry in read_dir(&self.path)? {
+            let path = entry?.path();
+
+            if path.extension().filter(|e| *e == "ck").is_none() {
+                continue;
+            }
+
+            let name = path.file_stem().and_then(|v| v.to_str()).unwrap_or("");
+            let coords: Vec<_> = name.split('_').map(|v| v.parse::<i32>()).collect();
+
+            if let [Ok(x), Ok(z)] = coords[..] {
+                regions.entry(region_coords(x, z)).or_default().push((x, z, path));
+            }
+        }
+
+        // each region file is written once
+        for files in regions.values() {
+            let mut chunks = Vec::new();
+
+            for (x, z, path) in files {
+                match Chunk::new_from_file(*x, *z, path) {
+                    Ok(chunk) => chunks.push(chunk),
+                    Err(e) => {
+                        let name = path.file_name().and_then(|v| v.to_str()).unwrap_or("chunk.ck");
+                        let quarantine = self.quarantine_path(name)
+                            .and_then(|target| {
+                                rename(path, &target)?;
+                                Ok(target)
+                            })
+                            .ok();
+
+                        self.report(ChunkError::Corrupted { x: *x, z: *z, quarantine, message: e.to_string() });
+                    }
+                }
+            }
+
+            if chunks.is_empty() {
+                continue;
+            }
+
+            self.write_region(&chunks.iter().collect::<Vec<_>>())?;
+
+            for (_, _, path) in files {
+                if path.exists() {
+                    remove_file(path)?;
+                }
+            }
+        }
 
         Ok(())
     }
 
-    pub fn load_or_generate_chunk(&mut self, x: i32, z: i32) -> Result<(), Box<dyn std::error::Error>> {
-        if main_world().chunk_loaded(x, z) {
-            return Ok(());
+    /**
+     * Unused path in the quarantine folder, where unreadable data is kept for inspection
+     */
+    fn quarantine_path(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let folder = self.path.join(QUARANTINE_FOLDER);
+        create_dir_all(&folder)?;
+
+        let mut path = folder.join(name);
+        let mut i = 1;
+
+        while path.exists() {
+            path = folder.join(format!("{}.{}", name, i));
+            i += 1;
         }
 
+        Ok(path)
+    }
+
+    /**
+     * Move the stored data of a chunk to the quarantine folder. Only if the header of the region
+     * file is unreadable (so that none of its chunks can be read), the whole region is moved:
+     * its loaded chunks are then marked as unsaved, to be written to a new region
+     */
+    fn quarantine_chunk(&self, world: &RwLock<World>, x: i32, z: i32) -> Result<PathBuf, Box<dyn std::error::Error>> {
         let path = self.chunk_file(x, z);
 
-        let chunk = if path.exists() {
-            Chunk::new_from_file(x, z, &path)?
-        } else if self.flat {
-            self.generator.generate_xz_flat(x, z)
-        } else {
-            self.generator.generate_xz(x, z)
-        };
+        match RegionFile::open(&path) {
+            Ok(mut region) => {
+                let target = self.quarantine_path(&format!("c.{}.{}.zlib", x, z))?;
+
+                write_atomic(&target, &region.read_raw_chunk(x, z)?.unwrap_or_default())?;
+
+                region.delete_chunk(x, z);
+                region.commit()?;
+
+                Ok(target)
+            }
+            Err(_) => {
+                let name = path.file_name().and_then(|v| v.to_str()).unwrap_or("region");
+                let target = self.quarantine_path(name)?;
+
+                rename(&path, &target)?;
+
+                let region = region_coords(x, z);
+                let mut world = world.write().unwrap();
+
+                let lost: Vec<_> = world.get_ref_chunks()
+                    .keys()
+                    .filter(|c| region_coords(c.x, c.y) == region)
+                    .cloned()
+                    .collect();
+
+                for coords in lost {
+                    if let Some(chunk) = world.chunk_mut(coords.x, coords.y) {
+                        Arc::make_mut(chunk).set_unsaved();
+                    }
+                }
+
+                Ok(target)
+            }
+        }
+    }
+
+    pub fn read_chunk(&self, x: i32, z: i32) -> Result<Option<Chunk>, Box<dyn std::error::Error>> {
+        let path = self.chunk_file(x, z);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        match RegionFile::open(&path)?.read_chunk(x, z)? {
+            Some(data) => Ok(Some(Chunk::from_bytes(x, z, &data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /**
+     * Read a stored chunk. Unreadable data is quarantined and reported,
+     * so that the chunk is generated again
+     */
+    fn load_chunk(&self, world: &RwLock<World>, x: i32, z: i32) -> Option<Chunk> {
+        match self.read_chunk(x, z) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let quarantine = self.quarantine_chunk(world, x, z);
+                let message = match &quarantine {
+                    Ok(_) => e.to_string(),
+                    Err(qe) => format!("{}, quarantine failed: {}", e, qe),
+                };
+
+                self.report(ChunkError::Corrupted { x, z, quarantine: quarantine.ok(), message });
+                None
+            }
+        }
+    }
+
+    fn by_region<'a>(chunks: &[&'a Chunk]) -> Vec<Vec<&'a Chunk>> {
+        let mut regions: HashMap<(i32, i32), Vec<&Chunk>> = HashMap::new();
+
+        for chunk in chunks {
+            let coords = chunk.coords();
+            regions.entry(region_coords(coords.x, coords.y)).or_default().push(chunk);
+        }
+
+        regions.values().cloned().collect()
+    }
+
+    /**
+     * Write chunks belonging to the same region, the region file is replaced once
+     */
+    fn write_region(&self, chunks: &[&Chunk]) -> Result<(), Box<dyn std::error::Error>> {
+        let coords = chunks[0].coords();
+        let mut region = RegionFile::open(&self.chunk_file(coords.x, coords.y))?;
+
+        for chunk in chunks {
+            let coords = chunk.coords();
+            region.write_chunk(coords.x, coords.y, &chunk.to_bytes())?;
+        }
+
+        region.commit()
+    }
+
+    pub fn write_chunks(&self, chunks: &[&Chunk]) -> Result<(), Box<dyn std::error::Error>> {
+        for region in ChunkManager::by_region(chunks) {
+            self.write_region(&region)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_chunk(&self, chunk: &Chunk) -> Result<(), Box<dyn std::error::Error>> {
+        self.write_region(&[chunk])
+    }
+
+    pub fn delete_chunk(&self, x: i32, z: i32) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.chunk_file(x, z);
+
+        if path.exists() {
+            let mut region = RegionFile::open(&path)?;
+
+            region.delete_chunk(x, z);
+            region.commit()?;
+        }
 
-        main_world().add_chunk(chunk);
         Ok(())
     }
 
-    pub fn load_or_generate_chunk_and_neighborhood(&mut self, x: i32, z: i32) -> Result<(), Box<dyn std::error::Error>> {
-        for dz in -1..=1 {
-            for dx in -1..=1 {
-                if dx == 0 && dz == 0 {
-                    continue;
+    /**
+     * Save every modified chunk. Chunks are written without holding the world lock,
+     * and are only marked as saved if they weren't modified in the meantime
+     */
+    pub fn save_all(&self, world: &RwLock<World>) {
+        let chunks: Vec<Arc<Chunk>> = world.read().unwrap()
+            .get_ref_chunks()
+            .values()
+            .filter(|c| c.unsaved())
+            .cloned()
+            .collect();
+
+        let saved = self.save_chunks(&chunks.iter().map(|c| &**c).collect::<Vec<_>>());
+
+        let mut world = world.write().unwrap();
+
+        for chunk in chunks {
+            let coords = chunk.coords();
+
+            if !saved.contains(&(coords.x, coords.y)) {
+                continue;
+            }
+
+            if let Some(current) = world.chunk_mut(coords.x, coords.y) {
+                if Arc::ptr_eq(current, &chunk) {
+                    drop(chunk);
+                    Arc::make_mut(current).set_saved();
+                }
+            }
+        }
+    }
+
+    /**
+     * Write chunks, reporting failures. Returns the coordinates of the saved chunks
+     */
+    fn save_chunks(&self, chunks: &[&Chunk]) -> HashSet<(i32, i32)> {
+        let mut saved = HashSet::new();
+
+        for region in ChunkManager::by_region(chunks) {
+            match self.write_region(&region) {
+                Ok(()) => saved.extend(region.iter().map(|c| (c.coords().x, c.coords().y))),
+                Err(e) => {
+                    for chunk in region {
+                        let coords = chunk.coords();
+                        self.report(ChunkError::Save { x: coords.x, z: coords.y, message: e.to_string() });
+                    }
                 }
+            }
+        }
+
+        saved
+    }
+
+    pub fn unload_chunks(&self, world: &RwLock<World>, coords: &[(i32, i32)]) {
+        let chunks: Vec<Arc<Chunk>> = {
+            let mut world = world.write().unwrap();
 
-                self.load_or_generate_chunk(x + dx, z + dz)?;
+            coords.iter()
+                .filter_map(|&(x, z)| world.remove_chunk(x, z))
+                .filter(|c| c.unsaved())
+                .collect()
+        };
+
+        self.save_chunks(&chunks.iter().map(|c| &**c).collect::<Vec<_>>());
+    }
+
+    /**
+     * Load the given chunks, or generate them (in parallel) if they aren't stored.
+     * Only the requested chunks are added to the world: generated chunks don't depend on
+     * the loaded ones, see `ChunkPipeline`
+     */
+    pub fn load_chunks(&mut self, world: &RwLock<World>, requests: &[(i32, i32)]) {
+        let missing: Vec<_> = {
+            let world = world.read().unwrap();
+            requests.iter().cloned().filter(|&(x, z)| !world.chunk_loaded(x, z)).collect()
+        };
+
+        let mut chunks = Vec::new();
+        let mut to_generate = Vec::new();
+
+        for (x, z) in missing {
+            match self.load_chunk(world, x, z) {
+                // chunks of older worlds may have been stored before being decorated
+                Some(chunk) if chunk.decorated() => chunks.push(chunk),
+                _ => to_generate.push((x, z)),
             }
         }
 
-        self.load_or_generate_chunk(x, z)?;
-        unsafe { Rc::get_mut_unchecked(&mut main_world().chunk_mut(x, z).unwrap()) }.decorate();
+        let pipeline = &mut self.pipeline;
 
-        Ok(())
+        match self.pool.install(|| pipeline.generate(&to_generate).map_err(|e| e.to_string())) {
+            Ok(generated) => chunks.extend(generated),
+            Err(message) => self.report(ChunkError::Generation(message)),
+        }
+
+        let mut world = world.write().unwrap();
+
+        for chunk in chunks {
+            let coords = chunk.coords();
+            world.add_chunk(Arc::new(chunk));
+
+            // faces of the neighbours against the new chunk are now hidden
+            for &(dx, dz) in &[(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                world.update_chunk(coords.x + dx, coords.y + dz);
+            }
+        }
+
+        let loaded: HashSet<_> = world.get_ref_chunks().keys().map(|c| (c.x, c.y)).collect();
+        drop(world);
+
+        self.pipeline.evict(&loaded);
     }
 }
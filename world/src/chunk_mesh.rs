@@ -1,8 +1,8 @@
 use std::collections::HashMap;
-use crate::{World, Chunk, FaceProperties, main_world};
+use crate::{world_to_chunk, Block, BlockRenderer, Chunk, FaceProperties, World};
 use nalgebra::{Vector2, Vector3, Vector4};
 
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, RwLock};
 use std::thread;
 
 fn add_vertice(v: Vector3<i32>, vertices: &mut Vec<[f32; 4]>, map: &mut HashMap<Vector3<i32>, u32>) -> u32 {
@@ -33,6 +33,42 @@ pub struct TriangleData {
     pub material: u32,
 }
 
+/**
+ * A chunk and its 4 neighbours, copied out of the world so that meshing doesn't hold its lock
+ */
+pub struct ChunkSnapshot {
+    chunk: Arc<Chunk>,
+    neighbours: Vec<Arc<Chunk>>,
+}
+
+impl ChunkSnapshot {
+    pub fn new(world: &World, x: i32, z: i32) -> Option<ChunkSnapshot> {
+        let chunk = world.chunk(x, z)?.clone();
+        let neighbours = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .filter_map(|(dx, dz)| world.chunk(x + dx, z + dz).cloned())
+            .collect();
+
+        Some(ChunkSnapshot { chunk, neighbours })
+    }
+
+    pub fn chunk(&self) -> &Chunk {
+        &self.chunk
+    }
+
+    /**
+     * Block at world coordinates, `None` outside the snapshot
+     */
+    pub fn block_at(&self, position: Vector3<i32>) -> Option<Block> {
+        let (x, z) = world_to_chunk(position);
+
+        std::iter::once(&self.chunk)
+            .chain(&self.neighbours)
+            .find(|chunk| chunk.coords() == Vector2::new(x, z))
+            .map(|chunk| chunk.block_at_vec(position))
+    }
+}
+
 pub struct ChunkMesh {
     // only for build
     vertices_map: HashMap<Vector3<i32>, u32>,
@@ -57,7 +93,8 @@ impl ChunkMesh {
         }
     }
 
-    pub fn from_chunk(world: &World, chunk: &Chunk) -> ChunkMesh {
+    pub fn from_chunk(renderers: &[BlockRenderer], snapshot: &ChunkSnapshot) -> ChunkMesh {
+        let chunk = snapshot.chunk();
         let (cx, cz) = {
             let cpos = chunk.position();
             (cpos.x, cpos.y)
@@ -70,8 +107,8 @@ impl ChunkMesh {
             for z in 0..16 {
                 for x in 0..16 {
                     let block_type = chunk.block_at_chunk(x, y, z);
-                    world.renderers[block_type as usize]
-                         .render(world, block_type, Vector3::new(x + cx, y, z + cz), &mut mesh);
+                    renderers[block_type as usize]
+                         .render(snapshot, block_type, Vector3::new(x + cx, y, z + cz), &mut mesh);
                 }
             }
         }
@@ -143,6 +180,7 @@ impl ChunkMesh {
 }
 
 pub struct ChunkMesher {
+    world: Arc<RwLock<World>>,
     request: mpsc::Receiver<(i32, i32)>,
     callback: mpsc::Sender<(i32, i32, ChunkMesh)>,
 }
@@ -150,9 +188,13 @@ pub struct ChunkMesher {
 impl ChunkMesher {
     pub fn run(&self) {
         while let Ok((x, z)) = self.request.recv() {
-            let world = main_world();
-            if let Some(chunk) = world.chunk(x, z) {
-                let mesh = ChunkMesh::from_chunk(world, chunk);
+            let (snapshot, renderers) = {
+                let world = self.world.read().unwrap();
+                (ChunkSnapshot::new(&world, x, z), world.renderers.clone())
+            };
+
+            if let Some(snapshot) = snapshot {
+                let mesh = ChunkMesh::from_chunk(&renderers, &snapshot);
                 self.callback.send((x, z, mesh)).expect("can't send meshing response");
             }
         }
@@ -165,12 +207,13 @@ pub struct ChunkMesherClient {
 }
 
 impl ChunkMesherClient {
-    pub fn new() -> Self {
+    pub fn new(world: Arc<RwLock<World>>) -> Self {
         let (request_sender, request_receiver) = mpsc::channel();
         let (response_sender, response_receiver) = mpsc::channel();
 
         thread::spawn(move || {
             ChunkMesher {
+                world,
                 request: request_receiver,
                 callback: response_sender,
             }.run()
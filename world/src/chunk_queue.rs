@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+
+use crate::Chunk;
+
+/**
+ * Maximum number of loads handed to the chunk manager at once: remaining
+ * requests stay in the queue, so that they can be re-prioritized or cancelled
+ */
+const LOAD_BATCH_SIZE: usize = 32;
+
+pub struct ChunkBatch {
+    pub unloads: Vec<(i32, i32)>,
+    /** Sorted by distance to the player */
+    pub loads: Vec<(i32, i32)>,
+    pub flushes: Vec<mpsc::Sender<()>>,
+    /** Unsaved chunks of a dropped world */
+    pub saves: Vec<Arc<Chunk>>,
+}
+
+struct QueueState {
+    loads: HashSet<(i32, i32)>,
+    unloads: HashSet<(i32, i32)>,
+    flushes: Vec<mpsc::Sender<()>>,
+    saves: Vec<Arc<Chunk>>,
+    center: (i32, i32),
+    closed: bool,
+}
+
+impl QueueState {
+    fn is_empty(&self) -> bool {
+        self.loads.is_empty() && self.unloads.is_empty() && self.flushes.is_empty() && self.saves.is_empty()
+    }
+}
+
+/**
+ * Pending chunk requests, shared between the world and the chunk manager.
+ * Loads are de-duplicated and served closest first; an unload cancels the pending load
+ * of the same chunk (and conversely)
+ */
+pub struct ChunkQueue {
+    state: Mutex<QueueState>,
+    available: Condvar,
+}
+
+impl Default for ChunkQueue {
+    fn default() -> ChunkQueue {
+        ChunkQueue::new()
+    }
+}
+
+impl ChunkQueue {
+    pub fn new() -> ChunkQueue {
+        ChunkQueue {
+            state: Mutex::new(QueueState {
+                loads: HashSet::new(),
+                unloads: HashSet::new(),
+                flushes: Vec::new(),
+                saves: Vec::new(),
+                center: (0, 0),
+                closed: false,
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    /**
+     * Set the chunk from which load priorities are computed (usually the player one)
+     */
+    pub fn set_center(&self, x: i32, z: i32) {
+        self.state.lock().unwrap().center = (x, z);
+    }
+
+    pub fn load(&self, x: i32, z: i32) {
+        let mut state = self.state.lock().unwrap();
+
+        state.unloads.remove(&(x, z));
+        state.loads.insert((x, z));
+
+        self.available.notify_one();
+    }
+
+    /**
+     * Cancel a pending unload of the chunk, returns whether there was one
+     */
+    pub fn cancel_unload(&self, x: i32, z: i32) -> bool {
+        self.state.lock().unwrap().unloads.remove(&(x, z))
+    }
+
+    pub fn unload(&self, x: i32, z: i32) {
+        let mut state = self.state.lock().unwrap();
+
+        state.loads.remove(&(x, z));
+        state.unloads.insert((x, z));
+
+        self.available.notify_one();
+    }
+
+    pub fn flush(&self, ack: mpsc::Sender<()>) {
+        let mut state = self.state.lock().unwrap();
+
+        state.flushes.push(ack);
+        self.available.notify_one();
+    }
+
+    /**
+     * Stop the requests: pending loads are dropped, while pending unloads and flushes are
+     * still served, along with the last chunks to save
+     */
+    pub fn close(&self, saves: Vec<Arc<Chunk>>) {
+        let mut state = self.state.lock().unwrap();
+
+        state.closed = true;
+        state.loads.clear();
+        state.saves.extend(saves);
+
+        self.available.notify_all();
+    }
+
+    /**
+     * Wait for requests, returns None once the queue is closed and drained
+     */
+    pub fn pop(&self) -> Option<ChunkBatch> {
+        let mut state = self.state.lock().unwrap();
+
+        while state.is_empty() && !state.closed {
+            state = self.available.wait(state).unwrap();
+        }
+
+        if state.closed && state.is_empty() {
+            return None;
+        }
+
+        let (cx, cz) = state.center;
+        let mut loads: Vec<_> = state.loads.iter().cloned().collect();
+
+        // far away coordinates don't fit in i32 once squared
+        loads.sort_by_key(|&(x, z)| ((x as i64 - cx as i64).pow(2) + (z as i64 - cz as i64).pow(2), x, z));
+        loads.truncate(LOAD_BATCH_SIZE);
+
+        for coords in &loads {
+            state.loads.remove(coords);
+        }
+
+        let mut unloads: Vec<_> = state.unloads.drain().collect();
+        unloads.sort();
+
+        Some(ChunkBatch {
+            unloads,
+            loads,
+            flushes: state.flushes.drain(..).collect(),
+            saves: state.saves.drain(..).collect(),
+        })
+    }
+}
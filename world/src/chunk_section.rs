@@ -0,0 +1,141 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Block;
+
+pub const SECTION_SIZE: i32 = 16;
+
+const SECTION_COUNT: usize = (SECTION_SIZE * SECTION_SIZE * SECTION_SIZE) as usize;
+
+/**
+ * 16x16x16 block storage: each block is an index in a small palette,
+ * bit-packed in 64 bits words (an index never overlaps two words)
+ */
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChunkSection {
+    palette: Vec<Block>,
+    bits: usize,
+    data: Vec<u64>,
+    non_air_blocks: usize,
+}
+
+fn bits_for(palette_size: usize) -> usize {
+    let mut bits = 0;
+
+    while (1 << bits) < palette_size {
+        bits += 1;
+    }
+
+    bits
+}
+
+impl Default for ChunkSection {
+    fn default() -> ChunkSection {
+        ChunkSection::new()
+    }
+}
+
+impl ChunkSection {
+    pub fn new() -> ChunkSection {
+        ChunkSection {
+            palette: vec![Block::Air],
+            bits: 0,
+            data: Vec::new(),
+            non_air_blocks: 0,
+        }
+    }
+
+    #[inline]
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        (x + z * SECTION_SIZE + y * SECTION_SIZE * SECTION_SIZE) as usize
+    }
+
+    #[inline]
+    fn palette_id(&self, i: usize) -> usize {
+        if self.bits == 0 {
+            return 0;
+        }
+
+        let per_word = 64 / self.bits;
+        let shift = (i % per_word) * self.bits;
+
+        ((self.data[i / per_word] >> shift) & ((1 << self.bits) - 1)) as usize
+    }
+
+    #[inline]
+    fn set_palette_id(&mut self, i: usize, id: usize) {
+        if self.bits == 0 {
+            return;
+        }
+
+        let per_word = 64 / self.bits;
+        let shift = (i % per_word) * self.bits;
+        let mask = ((1 << self.bits) - 1) << shift;
+        let word = &mut self.data[i / per_word];
+
+        *word = (*word & !mask) | ((id as u64) << shift);
+    }
+
+    /**
+     * Remove unused palette entries and pack the data with the minimal number of bits
+     */
+    fn repack(&mut self, min_palette_size: usize) {
+        let blocks: Vec<Block> = (0..SECTION_COUNT).map(|i| self.palette[self.palette_id(i)]).collect();
+
+        let mut palette = vec![Block::Air];
+
+        for block in &blocks {
+            if !palette.contains(block) {
+                palette.push(*block);
+            }
+        }
+
+        self.bits = bits_for(palette.len().max(min_palette_size));
+        self.palette = palette;
+        self.data = match self.bits {
+            0 => Vec::new(),
+            bits => vec![0; (SECTION_COUNT - 1) / (64 / bits) + 1],
+        };
+
+        for (i, block) in blocks.iter().enumerate() {
+            let id = self.palette.iter().position(|b| b == block).unwrap();
+            self.set_palette_id(i, id);
+        }
+    }
+
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Block {
+        self.palette[self.palette_id(ChunkSection::index(x, y, z))]
+    }
+
+    pub fn set_block_at(&mut self, x: i32, y: i32, z: i32, block: Block) {
+        let i = ChunkSection::index(x, y, z);
+        let previous = self.palette[self.palette_id(i)];
+
+        if previous == block {
+            return;
+        }
+
+        if previous == Block::Air {
+            self.non_air_blocks += 1;
+        } else if block == Block::Air {
+            self.non_air_blocks -= 1;
+        }
+
+        let id = match self.palette.iter().position(|b| *b == block) {
+            Some(id) => id,
+            None => {
+                if self.palette.len() == 1 << self.bits {
+                    self.repack(self.palette.len() + 1);
+                }
+
+                self.palette.push(block);
+                self.palette.len() - 1
+            }
+        };
+
+        self.set_palette_id(i, id);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.non_air_blocks == 0
+    }
+}
@@ -0,0 +1,152 @@
+use nalgebra::Vector3;
+use perlin::PerlinOctaves;
+use rand::{rngs::StdRng, SeedableRng};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::generator::{feature_seed, CAVE_FEATURE};
+use crate::{Block, Chunk, SEA_LEVEL};
+
+/** Caves are carved between these heights */
+const MIN_CAVE_Y: i32 = 5;
+const MAX_CAVE_Y: i32 = 128;
+
+/** Noise is sampled every 4 blocks and interpolated in between */
+const CELL: i32 = 4;
+const SAMPLES_XZ: usize = 16 / CELL as usize + 1;
+const SAMPLES_Y: usize = (MAX_CAVE_Y / CELL) as usize + 1;
+
+/** Tunnel half-width, in noise units, for a cave density of 1 */
+const TUNNEL_WIDTH: f32 = 0.8;
+
+/**
+ * Minimal thickness of ground kept above caves in columns below the sea level,
+ * so that caves don't punch through the floor of oceans and rivers
+ */
+const WATER_MARGIN: i32 = 6;
+
+#[inline]
+fn noise_amplitude() -> Vector3<f32> {
+    Vector3::new(0.3, 0.45, 0.3)
+}
+
+/**
+ * Carves tunnels in the generated terrain: tunnels follow the intersection of the zero
+ * surfaces of two 3D noises, where both are close to zero. Their width depends on the
+ * `BiomeType::cave_density` of the column
+ */
+pub struct CaveCarver {
+    noises: [PerlinOctaves; 2],
+}
+
+impl CaveCarver {
+    pub fn new(seed: i64) -> CaveCarver {
+        let mut rng = StdRng::seed_from_u64(feature_seed(seed, CAVE_FEATURE, 0, 0));
+
+        CaveCarver {
+            noises: [PerlinOctaves::new(3, &mut rng), PerlinOctaves::new(3, &mut rng)],
+        }
+    }
+
+    /**
+     * Sample both noises around the chunk, pairs are indexed by (x * SAMPLES_XZ + z) * SAMPLES_Y + y
+     */
+    fn sample(&self, chunk: &Chunk) -> Vec<(f32, f32)> {
+        let coords = chunk.coords();
+        let position = Vector3::new((coords.x * 16 / CELL) as f32, 0., (coords.y * 16 / CELL) as f32);
+        let size = Vector3::new(SAMPLES_XZ, SAMPLES_Y, SAMPLES_XZ);
+
+        let a = self.noises[0].noise(position, size, noise_amplitude());
+        let b = self.noises[1].noise(position, size, noise_amplitude());
+
+        a.into_iter().zip(b).collect()
+    }
+
+    /**
+     * Height from which the column can't be carved. Columns whose ground is below the sea level
+     * are under water: a margin of ground is kept above caves
+     */
+    fn carve_limit(chunk: &Chunk, x: i32, z: i32) -> i32 {
+        match chunk.block_at_chunk(x, SEA_LEVEL, z) {
+            Block::Air | Block::Water => (),
+            _ => return MAX_CAVE_Y,
+        }
+
+        for y in (0..SEA_LEVEL).rev() {
+            match chunk.block_at_chunk(x, y, z) {
+                Block::Air | Block::Water => continue,
+                _ => return y - WATER_MARGIN,
+            }
+        }
+
+        0
+    }
+
+    /**
+     * Carve the chunk, `neighbourhood` holds the uncarved chunks around it: the columns next to
+     * the chunk are checked for water too. Missing neighbours are considered dry
+     */
+    pub fn carve(&self, chunk: &mut Chunk, neighbourhood: &HashMap<(i32, i32), Arc<Chunk>>) {
+        let samples = self.sample(chunk);
+        let sample = |x: usize, y: usize, z: usize| samples[(x * SAMPLES_XZ + z) * SAMPLES_Y + y];
+        let coords = chunk.coords();
+
+        // limits also apply to the neighbouring columns, so that caves don't open on the side of water
+        let mut limits = [[MAX_CAVE_Y; 16]; 16];
+
+        for x in -1..=16 {
+            for z in -1..=16 {
+                let floor = if (0..16).contains(&x) && (0..16).contains(&z) {
+                    CaveCarver::carve_limit(chunk, x, z)
+                } else {
+                    match neighbourhood.get(&(coords.x + (x >> 4), coords.y + (z >> 4))) {
+                        Some(neighbour) => CaveCarver::carve_limit(neighbour, x & 15, z & 15),
+                        None => MAX_CAVE_Y,
+                    }
+                };
+
+                for (nx, nz) in &[(x, z), (x - 1, z), (x + 1, z), (x, z - 1), (x, z + 1)] {
+                    if *nx >= 0 && *nx < 16 && *nz >= 0 && *nz < 16 {
+                        let limit = &mut limits[*nx as usize][*nz as usize];
+                        *limit = (*limit).min(floor);
+                    }
+                }
+            }
+        }
+
+        for x in 0..16 {
+            for z in 0..16 {
+                let width = TUNNEL_WIDTH * chunk.biome_at(x, z).cave_density();
+                let limit = limits[x as usize][z as usize];
+
+                let (cx, tx) = ((x / CELL) as usize, (x % CELL) as f32 / CELL as f32);
+                let (cz, tz) = ((z / CELL) as usize, (z % CELL) as f32 / CELL as f32);
+
+                for y in MIN_CAVE_Y..limit {
+                    let (cy, ty) = ((y / CELL) as usize, (y % CELL) as f32 / CELL as f32);
+
+                    let (mut a, mut b) = (0.0, 0.0);
+
+                    for (i, wx) in [(0, 1. - tx), (1, tx)].iter() {
+                        for (j, wy) in [(0, 1. - ty), (1, ty)].iter() {
+                            for (k, wz) in [(0, 1. - tz), (1, tz)].iter() {
+                                let (sa, sb) = sample(cx + i, cy + j, cz + k);
+
+                                a += wx * wy * wz * sa;
+                                b += wx * wy * wz * sb;
+                            }
+                        }
+                    }
+
+                    if a * a + b * b < width * width {
+                        match chunk.block_at_chunk(x, y, z) {
+                            Block::Air | Block::Water => (),
+                            _ => chunk.set_block_at_chunk(x, y, z, Block::Air),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,94 @@
 use nalgebra::Vector3;
-use perlin::PerlinOctaves;
+use perlin::{NoiseSource, PerlinOctaves};
+use serde::{Deserialize, Serialize};
 
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
 
-use crate::generator::layers::{Layer, LayerResult};
+use crate::generator::layers::{Layer, LayerConfig, LayerGraph, LayerResult};
+use crate::generator::{feature_seed, CaveCarver, NoiseConfig, SURFACE_FEATURE};
 use crate::{Block, Chunk, SEA_LEVEL};
 
-use rand::SeedableRng;
+use rand::{rngs::StdRng, SeedableRng};
 
 pub struct ColumnProvider {
+    seed: i64,
+    settings: TerrainSettings,
     perlins: [PerlinOctaves; 4],
+    detail_noise: Option<Box<dyn NoiseSource>>,
+    /** Grids filled by the perlins for each chunk: 3 main noises and the depth noise */
+    noises: [Vec<f32>; 4],
     column_weights: [f32; 825],
     grass_colors: [Vector3<f32>; 25],
+    caves: CaveCarver,
     biome_provider: Box<Layer>,
     unzoomed_biome_provider: Box<Layer>,
 }
 
-const BASE_SIZE: f32 = 8.5;
-const Y_STRETCH: f32 = 12.;
+/**
+ * Terrain presets shipped with the game
+ */
+pub const TERRAIN_PRESETS: &[&str] = &["default", "amplified", "large_biomes", "islands", "rugged"];
+
+/**
+ * Shape of the terrain of the layered generator, see `terrain_presets.yaml`. Missing fields
+ * take their default value
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainSettings {
+    /** Biome layer graph: a layer preset or a layer graph file */
+    pub layers: String,
+    /** Height of the average ground, in noise cells of 8 blocks */
+    pub base_size: f32,
+    /** Vertical stretch of the density, lower values give higher terrain */
+    pub y_stretch: f32,
+    /** Blocks per unit of the main density noises, on each axis */
+    pub noise_amplitude: Vector3<f32>,
+    /** Divides `noise_amplitude` for the noise blending the two main noises */
+    pub main_noise_scale: Vector3<f32>,
+    /** Neighbouring columns weigh `1 / sqrt(distance² + falloff)` in the biome depth and scale */
+    pub nearby_column_falloff: f32,
+    /** Added to the density of the terrain (positive values give ground), sampled in blocks */
+    pub detail_noise: Option<NoiseConfig>,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> TerrainSettings {
+        TerrainSettings {
+            layers: "default".to_string(),
+            base_size: 8.5,
+            y_stretch: 12.,
+            noise_amplitude: Vector3::new(684.412, 684.412, 684.412),
+            main_noise_scale: Vector3::new(80., 160., 80.),
+            nearby_column_falloff: 0.2,
+            detail_noise: None,
+        }
+    }
+}
+
+impl TerrainSettings {
+    pub fn from_yaml(yaml: &str) -> Result<TerrainSettings, Box<dyn std::error::Error>> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn preset(name: &str) -> Option<TerrainSettings> {
+        let mut presets: HashMap<String, TerrainSettings> = serde_yaml::from_str(include_str!("terrain_presets.yaml")).unwrap();
+
+        presets.remove(name)
+    }
+
+    /**
+     * Load a preset, or a settings file if there is no preset with this name
+     */
+    pub fn load(name: &str) -> Result<TerrainSettings, Box<dyn std::error::Error>> {
+        match TerrainSettings::preset(name) {
+            Some(settings) => Ok(settings),
+            None => TerrainSettings::from_yaml(&fs::read_to_string(name)?),
+        }
+    }
+}
 
 #[inline]
 fn lerp(t: f32, a: f32, b: f32) -> f32 {
@@ -34,48 +105,77 @@ fn clamp_lerp(t: f32, a: f32, b: f32) -> f32 {
     lerp(t.min(1.0).max(0.0), a, b)
 }
 
-#[inline]
-fn noise_amplitude() -> Vector3<f32> {
-    Vector3::new(684.412, 684.412, 684.412)
-}
-
 #[inline]
 fn depth_noise_amplitude() -> Vector3<f32> {
     Vector3::new(200., 0.0, 200.)
 }
 
-#[inline]
-fn main_noise_scale() -> Vector3<f32> {
-    Vector3::new(80., 160., 80.)
-}
-
-fn nearby_column_mult(x: isize, y: isize) -> f32 {
-    10. / ((x * x + y * y) as f32 + 0.2).sqrt()
+fn nearby_column_mult(x: isize, y: isize, falloff: f32) -> f32 {
+    10. / ((x * x + y * y) as f32 + falloff).sqrt()
 }
 
 impl ColumnProvider {
-    pub fn new(seed: isize) -> ColumnProvider {
-        let (b, z) = Layer::create_generator(seed);
+    pub fn new(seed: i64) -> ColumnProvider {
+        ColumnProvider::with_settings(seed, &TerrainSettings::default()).unwrap()
+    }
+
+    /**
+     * Provider shaping the terrain with the given settings, failing if their layer graph can't
+     * be loaded
+     */
+    pub fn with_settings(seed: i64, settings: &TerrainSettings) -> Result<ColumnProvider, Box<dyn std::error::Error>> {
+        let layers = LayerConfig::load(&settings.layers)?.build(seed)?;
+
+        Ok(ColumnProvider::build(seed, layers, settings.clone()))
+    }
+
+    /**
+     * Provider using the biomes of the given layer graph, and the default shape of the terrain
+     */
+    pub fn with_layers(seed: i64, layers: LayerGraph) -> ColumnProvider {
+        ColumnProvider::build(seed, layers, TerrainSettings::default())
+    }
+
+    fn build(seed: i64, layers: LayerGraph, settings: TerrainSettings) -> ColumnProvider {
+        let (b, z) = layers.into_generators();
 
         let mut rng = SeedableRng::seed_from_u64(seed as u64);
 
+        let perlins = [
+            PerlinOctaves::new(8, &mut rng),
+            PerlinOctaves::new(16, &mut rng),
+            PerlinOctaves::new(16, &mut rng),
+            PerlinOctaves::new(16, &mut rng),
+        ];
+
+        let detail_noise = settings.detail_noise.as_ref().map(|n| n.build(&mut rng));
+
         ColumnProvider {
-            perlins: [
-                PerlinOctaves::new(8, &mut rng),
-                PerlinOctaves::new(16, &mut rng),
-                PerlinOctaves::new(16, &mut rng),
-                PerlinOctaves::new(16, &mut rng),
-            ],
+            seed,
+            settings,
+            perlins,
+            detail_noise,
+            noises: [vec![0.0; 825], vec![0.0; 825], vec![0.0; 825], vec![0.0; 25]],
             column_weights: [0.0; 825],
             grass_colors: [Vector3::zeros(); 25],
+            caves: CaveCarver::new(seed),
             biome_provider: z,
             unzoomed_biome_provider: b,
         }
     }
 
-    pub fn generate_chunk(&mut self, chunk: &mut Rc<Chunk>) {
-        let chunk = unsafe { Rc::get_mut_unchecked(chunk) };
+    /**
+     * Terrain and surface stages, caves are carved once the neighbouring chunks exist
+     */
+    pub fn generate_chunk(&mut self, chunk: &mut Chunk) {
+        self.generate_terrain(chunk);
+        self.generate_surface(chunk);
+    }
 
+    /**
+     * Terrain stage: general shape, biomes and grass colors
+     */
+    pub fn generate_terrain(&mut self, chunk: &mut Chunk) {
         let cx = chunk.coords().x as isize;
         let cy = chunk.coords().y as isize;
 
@@ -85,14 +185,35 @@ impl ColumnProvider {
 
         for x in 0..16 {
             for z in 0..16 {
-                biomes
-                    .biome(x, z)
-                    .generate_column(chunk, x as i32, z as i32);
                 *chunk.biome_at_mut(x as i32, z as i32) = biomes.biome(x, z);
             }
         }
     }
 
+    /**
+     * Surface stage: biome specific blocks of each column.
+     * The random generator only depends on the seed and the chunk coordinates
+     */
+    pub fn generate_surface(&mut self, chunk: &mut Chunk) {
+        let coords = chunk.coords();
+        let mut random = StdRng::seed_from_u64(feature_seed(self.seed, SURFACE_FEATURE, coords.x, coords.y));
+
+        for x in 0..16 {
+            for z in 0..16 {
+                let biome = *chunk.biome_at(x, z);
+                biome.generate_column(chunk, x, z, &mut random);
+            }
+        }
+    }
+
+    /**
+     * Cave stage: carves tunnels after the surface, so that cave floors are left as stone.
+     * `neighbourhood` holds the chunks around it, after their surface stage
+     */
+    pub fn generate_caves(&mut self, chunk: &mut Chunk, neighbourhood: &HashMap<(i32, i32), Arc<Chunk>>) {
+        self.caves.carve(chunk, neighbourhood);
+    }
+
     /**
      * Create chunk general shape, with only stone and water
      */
@@ -196,17 +317,17 @@ impl ColumnProvider {
     fn generate_weights(&mut self, biomes: &LayerResult, x: isize, z: isize) {
         let position = Vector3::new(x as f32, 0., z as f32);
         let noise_size = Vector3::new(5, 33, 5);
-        let amplitude = noise_amplitude();
+        let settings = &self.settings;
+        let detail_noise = &self.detail_noise;
+        let amplitude = settings.noise_amplitude;
 
-        let noise1 = self.perlins[0].noise(
-            position,
-            noise_size,
-            amplitude.component_div(&main_noise_scale()),
-        );
-        let noise2 = self.perlins[1].noise(position, noise_size, amplitude);
-        let noise3 = self.perlins[2].noise(position, noise_size, amplitude);
+        let [noise1, noise2, noise3, depth_noises] = &mut self.noises;
 
-        let depth_noises = self.perlins[2].noise2d(position, noise_size, depth_noise_amplitude());
+        self.perlins[0].fill(noise1, position, noise_size, amplitude.component_div(&settings.main_noise_scale));
+        self.perlins[1].fill(noise2, position, noise_size, amplitude);
+        self.perlins[2].fill(noise3, position, noise_size, amplitude);
+
+        self.perlins[2].fill2d(depth_noises, position, noise_size, depth_noise_amplitude());
 
         let mut id_2d = 0;
         let mut id_3d = 0;
@@ -225,7 +346,7 @@ impl ColumnProvider {
                     for dx in -2..=2 {
                         let biome = biomes.biome(x + dx + 2, z + dz + 2);
 
-                        let mut c_force = nearby_column_mult(dx, dz) / (2. + biome.elevation());
+                        let mut c_force = nearby_column_mult(dx, dz, settings.nearby_column_falloff) / (2. + biome.elevation());
 
                         if biome.elevation() > self_biome.elevation() {
                             c_force /= 2.;
@@ -239,7 +360,7 @@ impl ColumnProvider {
                     }
                 }
 
-                *self.grass_color_at(x as usize, z as usize) = grass_color / 25.0;
+                self.grass_colors[(x * 5 + z) as usize] = grass_color / 25.0;
 
                 scale = (scale / force) * 0.9 + 0.1;
                 depth = ((depth / force) * 4. - 1.0) / 8.;
@@ -261,11 +382,11 @@ impl ColumnProvider {
                     depth_noise = depth_noise.min(1.) / 8.;
                 }
 
-                let final_depth = (depth + 0.2 * depth_noise) * BASE_SIZE / 2. + BASE_SIZE;
+                let final_depth = (depth + 0.2 * depth_noise) * settings.base_size / 2. + settings.base_size;
 
                 // compute final values
                 for y in 0..33 {
-                    let threshold = match (y as f32 - final_depth) * Y_STRETCH * 128. / 256. / scale
+                    let threshold = match (y as f32 - final_depth) * settings.y_stretch * 128. / 256. / scale
                     {
                         t if t < 0.0 => 4. * t,
                         t => t,
@@ -277,6 +398,11 @@ impl ColumnProvider {
 
                     let mut res = clamp_lerp(t, a, b) - threshold;
 
+                    // noise cells are 4 blocks wide and 8 blocks high
+                    if let Some(noise) = detail_noise {
+                        res += noise.sample3d((position.x + x as f32) * 4., y as f32 * 8., (position.z + z as f32) * 4.);
+                    }
+
                     // reduce block probability at high altitude
                     if y > 29 {
                         let scale = (y as f32 - 29.) / 3.;
@@ -0,0 +1,179 @@
+use nalgebra::{Vector2, Vector3};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{world_to_chunk, BiomeType, Block, Chunk, MAX_HEIGHT};
+
+/**
+ * Biome densities are averaged over the columns at most this far from the decorated column
+ */
+const BLEND_RADIUS: i32 = 4;
+const BLEND_WIDTH: i32 = 16 + 2 * BLEND_RADIUS;
+
+/**
+ * Block written by a decorator, in world coordinates
+ */
+pub type DecorationWrite = (Vector3<i32>, Block);
+
+/**
+ * World seen by the decorators of a chunk: the carved chunk and its 8 neighbours, plus the
+ * blocks written by these decorators so far.
+ * Writes aren't applied to the chunks: they are buffered, and applied when chunks are finalized.
+ * Blocks outside the neighbourhood are air, and can't be written.
+ *
+ * Decorators are run for one biome at a time, and place features with the share of this biome
+ * around the targeted column (see `biome_weight`)
+ */
+pub struct DecorationContext<'a> {
+    center: Vector2<i32>,
+    neighbourhood: &'a HashMap<(i32, i32), Arc<Chunk>>,
+    /** Biomes of the chunk columns and of the columns at most BLEND_RADIUS around them */
+    biomes: Vec<Option<BiomeType>>,
+    biome: Option<BiomeType>,
+    written: HashMap<Vector3<i32>, Block>,
+    writes: Vec<DecorationWrite>,
+}
+
+impl<'a> DecorationContext<'a> {
+    /**
+     * `neighbourhood` must contain the carved chunk and its neighbours
+     */
+    pub fn new(x: i32, z: i32, neighbourhood: &'a HashMap<(i32, i32), Arc<Chunk>>) -> DecorationContext<'a> {
+        let mut biomes = Vec::with_capacity((BLEND_WIDTH * BLEND_WIDTH) as usize);
+
+        for wz in 0..BLEND_WIDTH {
+            for wx in 0..BLEND_WIDTH {
+                let bx = x * 16 - BLEND_RADIUS + wx;
+                let bz = z * 16 - BLEND_RADIUS + wz;
+
+                biomes.push(neighbourhood.get(&(bx >> 4, bz >> 4)).map(|c| *c.biome_at(bx & 15, bz & 15)));
+            }
+        }
+
+        DecorationContext {
+            center: Vector2::new(x, z),
+            neighbourhood,
+            biomes,
+            biome: None,
+            written: HashMap::new(),
+            writes: Vec::new(),
+        }
+    }
+
+    fn in_neighbourhood(&self, position: Vector3<i32>) -> Option<(i32, i32)> {
+        let (x, z) = world_to_chunk(position);
+
+        if (x - self.center.x).abs() > 1 || (z - self.center.y).abs() > 1 {
+            return None;
+        }
+
+        Some((x, z))
+    }
+
+    /**
+     * Biomes around the chunk, ordered by id
+     */
+    pub fn biomes(&self) -> Vec<BiomeType> {
+        let mut biomes: Vec<BiomeType> = self.biomes.iter().filter_map(|b| *b).collect();
+
+        biomes.sort_by_key(|b| b.id());
+        biomes.dedup();
+        biomes
+    }
+
+    /**
+     * Set the biome whose decorators are run
+     */
+    pub fn set_biome(&mut self, biome: BiomeType) {
+        self.biome = Some(biome);
+    }
+
+    /**
+     * Share of the current biome among the columns at most BLEND_RADIUS around the column (x, z)
+     * of the chunk: features are placed with this probability, so that densities fade across
+     * biome borders
+     */
+    pub fn biome_weight(&self, x: i32, z: i32) -> f32 {
+        if self.biome.is_none() {
+            return 1.0;
+        }
+
+        let wx = x - self.center.x * 16;
+        let wz = z - self.center.y * 16;
+
+        let mut count = 0;
+
+        for dz in -BLEND_RADIUS..=BLEND_RADIUS {
+            for dx in -BLEND_RADIUS..=BLEND_RADIUS {
+                let (bx, bz) = (wx + dx + BLEND_RADIUS, wz + dz + BLEND_RADIUS);
+
+                if (0..BLEND_WIDTH).contains(&bx) && (0..BLEND_WIDTH).contains(&bz) && self.biomes[(bx + bz * BLEND_WIDTH) as usize] == self.biome {
+                    count += 1;
+                }
+            }
+        }
+
+        count as f32 / ((2 * BLEND_RADIUS + 1) * (2 * BLEND_RADIUS + 1)) as f32
+    }
+
+    /**
+     * Whether the column (x, z) belongs to the current biome, for features placed away from
+     * the column their weight was drawn for. Always true when no biome is set
+     */
+    pub fn in_biome(&self, x: i32, z: i32) -> bool {
+        let biome = match self.biome {
+            Some(biome) => biome,
+            None => return true,
+        };
+
+        match self.in_neighbourhood(Vector3::new(x, 0, z)).and_then(|coords| self.neighbourhood.get(&coords)) {
+            Some(chunk) => *chunk.biome_at(x & 15, z & 15) == biome,
+            None => false,
+        }
+    }
+
+    pub fn highest_y(&self, x: i32, z: i32) -> i32 {
+        for y in (0..MAX_HEIGHT).rev() {
+            if self.unsafe_block_at_coords(x, y, z) != Block::Air {
+                return y;
+            }
+        }
+
+        0
+    }
+
+    pub fn unsafe_block_at(&self, position: Vector3<i32>) -> Block {
+        if let Some(block) = self.written.get(&position) {
+            return *block;
+        }
+
+        match self.in_neighbourhood(position).and_then(|c| self.neighbourhood.get(&c)) {
+            Some(chunk) => chunk.block_at_vec(position),
+            None => Block::Air,
+        }
+    }
+
+    pub fn unsafe_block_at_coords(&self, x: i32, y: i32, z: i32) -> Block {
+        self.unsafe_block_at(Vector3::new(x, y, z))
+    }
+
+    pub fn set_block_at(&mut self, position: Vector3<i32>, block: Block) {
+        if position.y < 0 || position.y >= MAX_HEIGHT || self.in_neighbourhood(position).is_none() {
+            return;
+        }
+
+        self.written.insert(position, block);
+        self.writes.push((position, block));
+    }
+
+    pub fn set_block_at_coords(&mut self, x: i32, y: i32, z: i32, block: Block) {
+        self.set_block_at(Vector3::new(x, y, z), block)
+    }
+
+    /**
+     * Buffered writes, in the order they were made
+     */
+    pub fn into_writes(self) -> Vec<DecorationWrite> {
+        self.writes
+    }
+}
@@ -1,13 +1,18 @@
 use nalgebra::{Vector2, Vector3};
-use crate::World;
 
 use rand::{SeedableRng, rngs::StdRng};
 
+use crate::generator::{feature_seed, DECORATOR_FEATURE};
+use crate::BiomeType;
+
+mod context;
 mod multi;
 mod tower_plant;
 mod plant_group;
 mod tree;
+mod vein;
 
+pub use context::*;
 pub use multi::*;
 
 #[macro_export]
@@ -21,13 +26,18 @@ macro_rules! multi_dec {
 pub use tower_plant::*;
 pub use plant_group::*;
 pub use tree::*;
+pub use vein::*;
 
 pub trait WorldDecorator {
-    fn decorate(&self, world: &mut World, random: &mut StdRng, position: Vector3<i32>);
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>);
 }
 
-pub fn decorator_random(world: &World, position: Vector2<i32>) -> StdRng {
-    let seed = (world.seed() as i32 | position.x | position.y) + position.x + position.y;
+/**
+ * Random stream of the given decorator of a biome, in a chunk
+ */
+pub fn decorator_random(world_seed: i64, position: Vector2<i32>, biome: BiomeType, decorator: usize) -> StdRng {
+    let feature = DECORATOR_FEATURE + ((biome.id() as u64) << 8) + decorator as u64;
+    let seed = feature_seed(world_seed, feature, position.x, position.y);
 
-    SeedableRng::seed_from_u64(seed as u64)
+    SeedableRng::seed_from_u64(seed)
 }
@@ -1,5 +1,5 @@
 use nalgebra::Vector3;
-use crate::{World, generator::decorators::WorldDecorator};
+use crate::generator::decorators::{DecorationContext, WorldDecorator};
 
 use rand::{Rng, rngs::StdRng};
 
@@ -18,10 +18,15 @@ impl DecoratorMulti {
 }
 
 impl WorldDecorator for DecoratorMulti {
-    fn decorate(&self, world: &mut World, random: &mut StdRng, position: Vector3<i32>) {
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
         for _ in 0..self.count {
             let dx = position.x + random.gen_range(0, 16);
             let dz = position.z + random.gen_range(0, 16);
+
+            if random.gen::<f32>() >= world.biome_weight(dx, dz) {
+                continue;
+            }
+
             let dy = world.highest_y(dx, dz) * 2;
 
             if dy > 0 {
@@ -1,5 +1,5 @@
 use nalgebra::Vector3;
-use crate::{Block, World, generator::decorators::WorldDecorator};
+use crate::{Block, generator::decorators::{DecorationContext, WorldDecorator}};
 
 use crate::multi_dec;
 
@@ -41,14 +41,15 @@ impl DecoratorPlantGroup {
 }
 
 impl WorldDecorator for DecoratorPlantGroup {
-    fn decorate(&self, world: &mut World, random: &mut StdRng, position: Vector3<i32>) {
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
         let mut y = position.y;
 
         while y > 0 && world.unsafe_block_at_coords(position.x, y, position.z) == Block::Air {
             y -= 1;
         }
 
-        let types = &self.block_type[random.gen_range(0, self.block_type.len())];
+        // indices are sampled as u64, usize ranges are sampled differently on 32 bits targets
+        let types = &self.block_type[random.gen_range(0, self.block_type.len() as u64) as usize];
 
         for _ in 0..self.count {
             let dx = position.x + random.gen_range(0, 8) - random.gen_range(0, 8);
@@ -56,7 +57,7 @@ impl WorldDecorator for DecoratorPlantGroup {
             let dz = position.z + random.gen_range(0, 8) - random.gen_range(0, 8);
 
             if !world.unsafe_block_at_coords(dx, dy, dz).is_tough() && self.is_support_accepted(world.unsafe_block_at_coords(dx, dy - 1, dz)) {
-                world.set_block_at_coords(dx, dy, dz, types[random.gen_range(0, types.len())]);
+                world.set_block_at_coords(dx, dy, dz, types[random.gen_range(0, types.len() as u64) as usize]);
             }
         }
     }
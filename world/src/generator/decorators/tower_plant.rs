@@ -1,5 +1,5 @@
 use nalgebra::Vector3;
-use crate::{Block, World, generator::decorators::WorldDecorator};
+use crate::{Block, generator::decorators::{DecorationContext, WorldDecorator}};
 
 use crate::multi_dec;
 
@@ -22,7 +22,7 @@ impl DecoratorTowerPlant {
         }, count)
     }
 
-    fn is_empty_arround(&self, world: &World, position: Vector3<i32>) -> bool {
+    fn is_empty_arround(&self, world: &DecorationContext, position: Vector3<i32>) -> bool {
         for dx in -1..=1 {
             for dz in -1..=1 {
                 if world.unsafe_block_at(Vector3::new(position.x + dx, position.y, position.z + dz)) != Block::Air {
@@ -36,12 +36,16 @@ impl DecoratorTowerPlant {
 }
 
 impl WorldDecorator for DecoratorTowerPlant {
-    fn decorate(&self, world: &mut World, random: &mut StdRng, position: Vector3<i32>) {
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
         for _ in 0..10 {
             let dx = position.x + random.gen_range(0, 8) - random.gen_range(0, 8);
             let dy = position.y + random.gen_range(0, 4) - random.gen_range(0, 4);
             let dz = position.z + random.gen_range(0, 8) - random.gen_range(0, 8);
 
+            if !world.in_biome(dx, dz) {
+                continue;
+            }
+
             if world.unsafe_block_at_coords(dx, dy - 1, dz) != self.accepted_support {
                 continue;
             }
@@ -1,8 +1,10 @@
 use nalgebra::{Vector2, Vector3};
-use crate::{Block, World, generator::decorators::WorldDecorator};
+use crate::{Block, generator::decorators::{DecorationContext, WorldDecorator}};
 
 use crate::multi_dec;
 
+use serde::Deserialize;
+
 use rand::{Rng, rngs::StdRng};
 
 struct BuffBlock {
@@ -34,7 +36,7 @@ impl BuffBlock {
         }
     }
 
-    pub fn set(&self, world: &mut World, snow: bool) {
+    pub fn set(&self, world: &mut DecorationContext, snow: bool) {
         let block_at = world.unsafe_block_at_coords(self.x, self.y, self.z);
         let block_at_up = world.unsafe_block_at_coords(self.x, self.y + 1, self.z);
 
@@ -48,6 +50,7 @@ impl BuffBlock {
     }
 }
 
+#[derive(Copy, Clone, Debug, Deserialize)]
 pub enum ForestType {
     Classic,
     Normal,
@@ -187,7 +190,7 @@ impl DecoratorTree {
         !block.is_tough()
     }
 
-    fn can_be_placed_here(&self, world: &mut World, x: i32, y: i32, z: i32) -> bool {
+    fn can_be_placed_here(&self, world: &mut DecorationContext, x: i32, y: i32, z: i32) -> bool {
         for dx in -1..=1 {
             for dz in -1..=1 {
                 let block = world.unsafe_block_at_coords(x + dx, y, z + dz);
@@ -214,7 +217,7 @@ impl DecoratorTree {
         }
     }
 
-    fn generate_leaves(&self, world: &mut World, result: &mut Vec<BuffBlock>, random: &mut StdRng, x: i32, y: i32, z: i32, tree_type: (Block, Block)) {
+    fn generate_leaves(&self, world: &mut DecorationContext, result: &mut Vec<BuffBlock>, random: &mut StdRng, x: i32, y: i32, z: i32, tree_type: (Block, Block)) {
         let mut width = 1;
 
         for dy in (-3..0).rev() {
@@ -232,7 +235,7 @@ impl DecoratorTree {
         }
     }
 
-    fn generate_branch(&self, world: &mut World, random: &mut StdRng, tree_type: (Block, Block), mut pos: Vector3<i32>, diff: Vector2<i32>) -> Vec<BuffBlock> {
+    fn generate_branch(&self, world: &mut DecorationContext, random: &mut StdRng, tree_type: (Block, Block), mut pos: Vector3<i32>, diff: Vector2<i32>) -> Vec<BuffBlock> {
         let mut result: Vec<BuffBlock> = Vec::new();
         let mut generated = true;
         let mut count = 0;
@@ -261,7 +264,7 @@ impl DecoratorTree {
         result
     }
 
-    fn generate_tree(&self, world: &mut World, random: &mut StdRng, size: i32, height: i32, x: i32, y: i32, z: i32) -> Vec<BuffBlock> {
+    fn generate_tree(&self, world: &mut DecorationContext, random: &mut StdRng, size: i32, height: i32, x: i32, y: i32, z: i32) -> Vec<BuffBlock> {
         let mut result: Vec<BuffBlock> = Vec::new();
         let mut branches = Vec::new();
         let mut generated = true;
@@ -315,13 +318,13 @@ impl DecoratorTree {
 }
 
 impl WorldDecorator for DecoratorTree {
-    fn decorate(&self, world: &mut World, random: &mut StdRng, position: Vector3<i32>) {
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
         for _ in 0..10 {
             let dx = position.x + random.gen_range(0, 8) - random.gen_range(0, 8);
             let dy = position.y + random.gen_range(0, 4) - random.gen_range(0, 4);
             let dz = position.z + random.gen_range(0, 8) - random.gen_range(0, 8);
 
-            if dy < 0 {
+            if dy < 0 || !world.in_biome(dx, dz) {
                 continue;
             }
 
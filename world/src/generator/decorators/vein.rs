@@ -0,0 +1,144 @@
+use nalgebra::Vector3;
+use crate::{Block, SEA_LEVEL, generator::decorators::{DecorationContext, WorldDecorator}};
+
+use rand::{Rng, rngs::StdRng};
+
+/**
+ * Blobs of a block replacing underground blocks: each vein is a string of spheres along
+ * a random segment, as Minecraft ores
+ */
+pub struct DecoratorVein {
+    block_type: Block,
+    replaced: Vec<Block>,
+    /** Approximate number of blocks of a vein */
+    size: usize,
+    min_y: i32,
+    max_y: i32,
+    /** Veins tried per chunk */
+    count: usize,
+}
+
+impl DecoratorVein {
+    fn boxed(block_type: Block, size: usize, min_y: i32, max_y: i32, count: usize) -> Box<dyn WorldDecorator + Sync> {
+        Box::new(DecoratorVein {
+            block_type,
+            replaced: vec![Block::Stone],
+            size,
+            min_y,
+            max_y,
+            count,
+        })
+    }
+
+    pub fn coal(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::CoalOre, 17, 5, 128, count)
+    }
+
+    pub fn iron(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::IronOre, 9, 5, 64, count)
+    }
+
+    pub fn gold(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::GoldOre, 9, 5, 32, count)
+    }
+
+    pub fn granite(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::Granite, 33, 5, 80, count)
+    }
+
+    pub fn andesite(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::Andesite, 33, 5, 80, count)
+    }
+
+    pub fn diorite(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        DecoratorVein::boxed(Block::Diorite, 33, 5, 80, count)
+    }
+
+    pub fn gravel(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        Box::new(DecoratorVein {
+            block_type: Block::Gravel,
+            replaced: vec![Block::Stone, Block::Dirt],
+            size: 33,
+            min_y: 5,
+            max_y: 128,
+            count,
+        })
+    }
+
+    /**
+     * Clay pockets in the ground of shores and river beds
+     */
+    pub fn clay(count: usize) -> Box<dyn WorldDecorator + Sync> {
+        Box::new(DecoratorVein {
+            block_type: Block::Clay,
+            replaced: vec![Block::Dirt, Block::Sand, Block::Gravel],
+            size: 24,
+            min_y: SEA_LEVEL - 12,
+            max_y: SEA_LEVEL + 2,
+            count,
+        })
+    }
+
+    fn sphere(center: Vector3<f32>, radius: f32, blocks: &mut Vec<(i32, i32, i32)>) {
+        let min = (center - Vector3::repeat(radius)).map(|v| v.floor() as i32);
+        let max = (center + Vector3::repeat(radius)).map(|v| v.floor() as i32);
+
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                for z in min.z..=max.z {
+                    let offset = (Vector3::new(x as f32, y as f32, z as f32) + Vector3::repeat(0.5) - center) / radius;
+
+                    if offset.norm_squared() < 1.0 {
+                        blocks.push((x, y, z));
+                    }
+                }
+            }
+        }
+    }
+
+    fn place_vein(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
+        let size = self.size as f32;
+        let angle = random.gen::<f32>() * std::f32::consts::PI;
+
+        let center = position.map(|v| v as f32) + Vector3::repeat(0.5);
+        let half = Vector3::new(angle.sin(), 0., angle.cos()) * size / 8.;
+
+        let start = center + half + Vector3::new(0., random.gen_range(-2., 2.), 0.);
+        let end = center - half + Vector3::new(0., random.gen_range(-2., 2.), 0.);
+
+        let mut blocks = Vec::new();
+
+        for i in 0..self.size {
+            let t = i as f32 / size;
+            let radius = ((t * std::f32::consts::PI).sin() + 1.) * (random.gen::<f32>() * size / 16. + 1.) / 2.;
+
+            DecoratorVein::sphere(start + (end - start) * t, radius, &mut blocks);
+        }
+
+        // spheres overlap a lot
+        blocks.sort();
+        blocks.dedup();
+
+        for (x, y, z) in blocks {
+            if self.replaced.contains(&world.unsafe_block_at_coords(x, y, z)) {
+                world.set_block_at_coords(x, y, z, self.block_type);
+            }
+        }
+    }
+}
+
+impl WorldDecorator for DecoratorVein {
+    fn decorate(&self, world: &mut DecorationContext, random: &mut StdRng, position: Vector3<i32>) {
+        for _ in 0..self.count {
+            let x = position.x + random.gen_range(0, 16);
+            let y = random.gen_range(self.min_y, self.max_y);
+            let z = position.z + random.gen_range(0, 16);
+
+            if random.gen::<f32>() >= world.biome_weight(x, z) {
+                continue;
+            }
+
+            self.place_vein(world, random, Vector3::new(x, y, z));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::generator::TerrainGenerator;
+use crate::{BiomeType, Block, Chunk, MAX_HEIGHT};
+
+/**
+ * Superflat presets shipped with the game
+ */
+pub const FLAT_PRESETS: &[&str] = &["classic", "plains", "desert", "water", "bare"];
+
+/**
+ * Layout of the chunks of a flat world, see `flat_presets.yaml`
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FlatPreset {
+    /** From the bottom of the world: block and thickness */
+    pub layers: Vec<(Block, usize)>,
+    /** Name of the biome of every column */
+    #[serde(default)]
+    pub biome: Option<String>,
+    /** Whether the decorators of the biome run */
+    #[serde(default)]
+    pub decorated: bool,
+}
+
+impl Default for FlatPreset {
+    /**
+     * Three layers of stone under a layer of grass, without decoration
+     */
+    fn default() -> FlatPreset {
+        FlatPreset {
+            layers: vec![(Block::Stone, 3), (Block::Grass, 1)],
+            biome: None,
+            decorated: false,
+        }
+    }
+}
+
+impl FlatPreset {
+    pub fn from_yaml(yaml: &str) -> Result<FlatPreset, Box<dyn std::error::Error>> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn preset(name: &str) -> Option<FlatPreset> {
+        let mut presets: HashMap<String, FlatPreset> = serde_yaml::from_str(include_str!("flat_presets.yaml")).unwrap();
+
+        presets.remove(name)
+    }
+
+    /**
+     * Load a preset, or a preset file if there is no preset with this name
+     */
+    pub fn load(name: &str) -> Result<FlatPreset, Box<dyn std::error::Error>> {
+        match FlatPreset::preset(name) {
+            Some(preset) => Ok(preset),
+            None => FlatPreset::from_yaml(&fs::read_to_string(name)?),
+        }
+    }
+}
+
+pub struct FlatGenerator {
+    /** Block of each height, up to the top layer */
+    column: Vec<Block>,
+    biome: Option<BiomeType>,
+    decorated: bool,
+}
+
+impl FlatGenerator {
+    pub fn new(preset: &FlatPreset) -> Result<FlatGenerator, Box<dyn std::error::Error>> {
+        let mut column: Vec<Block> = Vec::new();
+
+        for &(block, thickness) in &preset.layers {
+            column.resize(column.len() + thickness, block);
+        }
+
+        if column.len() > MAX_HEIGHT as usize {
+            return Err(format!("flat layers are {} blocks high, the maximum is {}", column.len(), MAX_HEIGHT).into());
+        }
+
+        let biome = match &preset.biome {
+            Some(name) => Some(BiomeType::from_name(name).ok_or_else(|| format!("unknown biome {}", name))?),
+            None => None,
+        };
+
+        Ok(FlatGenerator {
+            column,
+            biome,
+            decorated: preset.decorated,
+        })
+    }
+}
+
+impl TerrainGenerator for FlatGenerator {
+    fn generate_xz(&mut self, x: i32, z: i32) -> Chunk {
+        let mut chunk = Chunk::new(x, z);
+
+        for x in 0..16 {
+            for z in 0..16 {
+                for (y, block) in self.column.iter().enumerate() {
+                    chunk.set_block_at_chunk(x, y as i32, z, *block)
+                }
+
+                if let Some(biome) = self.biome {
+                    *chunk.biome_at_mut(x, z) = biome;
+                    chunk.set_grass_color(x, z, biome.grass_color());
+                }
+            }
+        }
+
+        chunk
+    }
+
+    fn decorated(&self) -> bool {
+        self.decorated
+    }
+}
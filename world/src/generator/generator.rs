@@ -1,46 +1,46 @@
-use crate::{generator::ColumnProvider, Chunk, Block};
-use std::rc::Rc;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{generator::layers::LayerGraph, generator::ColumnProvider, generator::TerrainSettings, Chunk};
 
 pub struct ChunkGenerator {
     provider: ColumnProvider,
 }
 
 impl ChunkGenerator {
-    pub fn new(seed: isize) -> ChunkGenerator {
+    pub fn new(seed: i64) -> ChunkGenerator {
         ChunkGenerator {
             provider: ColumnProvider::new(seed),
         }
     }
 
-    pub fn generate(&mut self, chunk: &mut Rc<Chunk>) {
+    pub fn with_settings(seed: i64, settings: &TerrainSettings) -> Result<ChunkGenerator, Box<dyn std::error::Error>> {
+        Ok(ChunkGenerator {
+            provider: ColumnProvider::with_settings(seed, settings)?,
+        })
+    }
+
+    pub fn with_layers(seed: i64, layers: LayerGraph) -> ChunkGenerator {
+        ChunkGenerator {
+            provider: ColumnProvider::with_layers(seed, layers),
+        }
+    }
+
+    pub fn generate(&mut self, chunk: &mut Chunk) {
         self.provider.generate_chunk(chunk);
     }
 
-    pub fn generate_xz(&mut self, x: i32, z: i32) -> Rc<Chunk> {
-        let mut result = Chunk::new_empty(x, z);
+    pub fn generate_xz(&mut self, x: i32, z: i32) -> Chunk {
+        let mut result = Chunk::new(x, z);
 
         self.generate(&mut result);
         result
     }
 
-    pub fn generate_xz_flat(&mut self, x: i32, z: i32) -> Rc<Chunk> {
-        let mut result = Chunk::new_empty(x, z);
-        let chunk = unsafe { Rc::get_mut_unchecked(&mut result) };
-
-        for x in 0..16 {
-            for z in 0..16 {
-                for y in 0..4 {
-                    let t = if y == 3 {
-                        Block::Grass
-                    } else {
-                        Block::Stone
-                    };
-
-                    chunk.set_block_at_chunk(x, y, z, t)
-                }
-            }
-        }
-
-        result
+    /**
+     * Carve the caves of a generated chunk, `neighbourhood` holds the generated chunks around it
+     */
+    pub fn carve(&mut self, chunk: &mut Chunk, neighbourhood: &HashMap<(i32, i32), Arc<Chunk>>) {
+        self.provider.generate_caves(chunk, neighbourhood);
     }
 }
@@ -59,7 +59,7 @@ impl LayerImpl for LayerBiomeType {
                 0
             } else {
                 data.rand.init_local(x + dx, y + dy);
-                *data.rand.peek(&BiomeGroup::get(val - 1).biomes()) as isize
+                data.rand.peek(BiomeGroup::get(val - 1).biomes()).id()
             };
         })
     }
@@ -100,7 +100,7 @@ impl LayerImpl for LayerBiomeEdge {
             let s = *parent.at(dx + 1, dy + 1);
 
             *v = if neighbours.iter().any(|v| is_ocean(*v)) && !is_ocean(s) {
-                BiomeType::Beach as isize
+                BiomeType::Beach.id()
             } else  {
                 s
             };
@@ -139,7 +139,7 @@ impl LayerImpl for LayerBiomeHills {
             let b = *sentry.at(dx + 1, dy + 1);
 
             *v = if !is_ocean(a) && (b - 2) % 7 == 0 {
-                BiomeType::from_id(a).get_hills_version() as isize
+                BiomeType::from_id(a).get_hills_version().id()
             } else {
                 a
             };
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::generator::layers::{LayerData, LayerImpl, LayerResult};
+
+/**
+ * Side of the cached tiles, in cells of the cached layer
+ */
+pub const CACHE_TILE_SIZE: usize = 32;
+
+type Tile = Arc<Vec<isize>>;
+
+struct Tiles {
+    capacity: usize,
+    /** Incremented on each access, the least recently used tile is evicted first */
+    clock: u64,
+    tiles: HashMap<(isize, isize), (u64, Tile)>,
+}
+
+/**
+ * Keep the result of its parent by tiles, so that overlapping requests (neighbouring chunks, or
+ * the zoomed and unzoomed biome providers) don't regenerate the whole parent chain.
+ * Clones share the same tiles
+ */
+#[derive(Clone)]
+pub struct LayerCache {
+    tiles: Arc<Mutex<Tiles>>,
+}
+
+impl LayerCache {
+    /**
+     * Cache holding at most `capacity` tiles
+     */
+    pub fn new(capacity: usize) -> LayerCache {
+        LayerCache {
+            tiles: Arc::new(Mutex::new(Tiles {
+                capacity: capacity.max(1),
+                clock: 0,
+                tiles: HashMap::new(),
+            })),
+        }
+    }
+
+    fn tile(&self, data: &mut LayerData, tx: isize, ty: isize) -> Tile {
+        {
+            let mut tiles = self.tiles.lock().unwrap();
+            tiles.clock += 1;
+
+            let clock = tiles.clock;
+
+            if let Some((last_use, tile)) = tiles.tiles.get_mut(&(tx, ty)) {
+                *last_use = clock;
+                return tile.clone();
+            }
+        }
+
+        // generated without the lock, so that clones used on other threads aren't blocked
+        let size = CACHE_TILE_SIZE as isize;
+        let tile = Arc::new(
+            data.parent
+                .as_mut()
+                .unwrap()
+                .generate(tx * size, ty * size, CACHE_TILE_SIZE, CACHE_TILE_SIZE)
+                .data,
+        );
+
+        let mut tiles = self.tiles.lock().unwrap();
+
+        if tiles.tiles.len() >= tiles.capacity {
+            let oldest = tiles.tiles.iter().min_by_key(|(_, (last_use, _))| *last_use).map(|(k, _)| *k);
+
+            if let Some(oldest) = oldest {
+                tiles.tiles.remove(&oldest);
+            }
+        }
+
+        let clock = tiles.clock;
+        tiles.tiles.insert((tx, ty), (clock, tile.clone()));
+
+        tile
+    }
+}
+
+impl LayerImpl for LayerCache {
+    fn generate(&self, data: &mut LayerData, x: isize, y: isize, result: &mut LayerResult) {
+        let size = CACHE_TILE_SIZE as isize;
+        let (width, height) = (result.width as isize, result.height as isize);
+
+        for ty in y.div_euclid(size)..=(y + height - 1).div_euclid(size) {
+            for tx in x.div_euclid(size)..=(x + width - 1).div_euclid(size) {
+                let tile = self.tile(data, tx, ty);
+
+                // overlap of the tile and the result, in result coordinates
+                let start_x = (tx * size - x).max(0);
+                let end_x = (tx * size + size - x).min(width);
+
+                for dy in (ty * size - y).max(0)..(ty * size + size - y).min(height) {
+                    let row = (y + dy - ty * size) * size + x - tx * size;
+                    let start = dy * width;
+
+                    result.data[(start + start_x) as usize..(start + end_x) as usize]
+                        .copy_from_slice(&tile[(row + start_x) as usize..(row + end_x) as usize]);
+                }
+            }
+        }
+    }
+
+    fn clone_layer(&self) -> Box<dyn LayerImpl> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,266 @@
+use serde::Deserialize;
+
+use std::fs;
+
+use crate::generator::layers::*;
+use crate::generator::SimpleRandom;
+
+/**
+ * Layer graphs shipped with the game
+ */
+pub const LAYER_PRESETS: &[&str] = &["default", "large_biomes", "more_ocean", "no_rivers"];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerKind {
+    Island,
+    AddIsland,
+    Zoom,
+    FuzzyZoom,
+    ZoomVoronoi,
+    TooMuchOceans,
+    DeepOcean,
+    BiomeGroup,
+    BiomeType,
+    BiomeEdge,
+    Hills,
+    Cleaner,
+    Smooth,
+    River,
+    RiverApply,
+}
+
+impl LayerKind {
+    /**
+     * Resolution of the layer relatively to its parent
+     */
+    pub fn zoom(&self) -> usize {
+        match self {
+            LayerKind::Zoom | LayerKind::FuzzyZoom => 2,
+            LayerKind::ZoomVoronoi => 4,
+            _ => 1,
+        }
+    }
+
+    fn has_parent(&self) -> bool {
+        *self != LayerKind::Island
+    }
+
+    fn has_source(&self) -> bool {
+        *self == LayerKind::Hills || *self == LayerKind::RiverApply
+    }
+
+    fn create(&self, source: Option<Box<Layer>>) -> Box<dyn LayerImpl> {
+        match self {
+            LayerKind::Island => Box::new(LayerIsland::new()),
+            LayerKind::AddIsland => Box::new(LayerAddIsland::new()),
+            LayerKind::Zoom => Box::new(LayerZoom::new()),
+            LayerKind::FuzzyZoom => Box::new(LayerZoom::new_fuzzy()),
+            LayerKind::ZoomVoronoi => Box::new(LayerZoomVoronoi::new()),
+            LayerKind::TooMuchOceans => Box::new(LayerTooMuchOceans::new()),
+            LayerKind::DeepOcean => Box::new(LayerDeepOcean::new()),
+            LayerKind::BiomeGroup => Box::new(LayerBiomeGroup::new()),
+            LayerKind::BiomeType => Box::new(LayerBiomeType::new()),
+            LayerKind::BiomeEdge => Box::new(LayerBiomeEdge::new()),
+            LayerKind::Hills => Box::new(LayerBiomeHills::new(source.unwrap())),
+            LayerKind::Cleaner => Box::new(LayerCleaner::new()),
+            LayerKind::Smooth => Box::new(LayerSmooth::new()),
+            LayerKind::River => Box::new(LayerRiver::new()),
+            LayerKind::RiverApply => Box::new(LayerRiverApply::new(source.unwrap())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LayerNodeConfig {
+    name: String,
+    kind: LayerKind,
+    /** Mixed with the world seed to seed the random generator of the layer */
+    salt: i64,
+    /** Previous layer by default */
+    parent: Option<String>,
+    /** Second input of `hills` and `river_apply` layers */
+    source: Option<String>,
+    /** Number of tiles of the result kept in memory, shared by every layer using this one */
+    cache: Option<usize>,
+}
+
+/**
+ * Declarative biome layer graph, see `presets/default.yaml`
+ */
+#[derive(Clone, Debug, Deserialize)]
+pub struct LayerConfig {
+    /** Layer giving the biomes at a 4 blocks resolution */
+    biomes: String,
+    /** Layer giving the biome of each block */
+    zoomed: String,
+    layers: Vec<LayerNodeConfig>,
+}
+
+/**
+ * Layer of a built graph, with its own copy of the layers it depends on
+ */
+#[derive(Clone)]
+pub struct LayerStage {
+    pub name: String,
+    pub kind: LayerKind,
+    /** Blocks per cell of the layer */
+    pub scale: usize,
+    /** Whether the values of the layer are biome ids, instead of land, climates or noise */
+    pub biomes: bool,
+    pub layer: Box<Layer>,
+}
+
+/**
+ * Every stage of a layer graph, so that intermediate results can be inspected
+ */
+#[derive(Clone)]
+pub struct LayerGraph {
+    stages: Vec<LayerStage>,
+    biomes: usize,
+    zoomed: usize,
+}
+
+impl LayerConfig {
+    pub fn from_yaml(yaml: &str) -> Result<LayerConfig, Box<dyn std::error::Error>> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn preset(name: &str) -> Option<LayerConfig> {
+        let yaml = match name {
+            "default" => include_str!("presets/default.yaml"),
+            "large_biomes" => include_str!("presets/large_biomes.yaml"),
+            "more_ocean" => include_str!("presets/more_ocean.yaml"),
+            "no_rivers" => include_str!("presets/no_rivers.yaml"),
+            _ => return None,
+        };
+
+        Some(LayerConfig::from_yaml(yaml).unwrap())
+    }
+
+    /**
+     * Load a preset, or a layer graph file if there is no preset with this name
+     */
+    pub fn load(name: &str) -> Result<LayerConfig, Box<dyn std::error::Error>> {
+        match LayerConfig::preset(name) {
+            Some(config) => Ok(config),
+            None => LayerConfig::from_yaml(&fs::read_to_string(name)?),
+        }
+    }
+
+    /**
+     * Same graph, with every layer recomputed on each request
+     */
+    pub fn without_caches(mut self) -> LayerConfig {
+        for node in &mut self.layers {
+            node.cache = None;
+        }
+
+        self
+    }
+
+    pub fn build(&self, world_seed: i64) -> Result<LayerGraph, Box<dyn std::error::Error>> {
+        let mut stages: Vec<LayerStage> = Vec::with_capacity(self.layers.len());
+        let mut zooms = Vec::with_capacity(self.layers.len());
+
+        let find = |stages: &[LayerStage], name: &str| {
+            stages
+                .iter()
+                .position(|s| s.name == name)
+                .ok_or_else(|| format!("unknown layer {} (layers must be defined after their inputs)", name))
+        };
+
+        for node in &self.layers {
+            if stages.iter().any(|s| s.name == node.name) {
+                return Err(format!("layer {} is defined twice", node.name).into());
+            }
+
+            let parent = match (node.kind.has_parent(), &node.parent, stages.last()) {
+                (false, None, _) => None,
+                (false, Some(_), _) => return Err(format!("layer {} can't have a parent", node.name).into()),
+                (true, Some(name), _) => Some(find(&stages, name)?),
+                (true, None, Some(_)) => Some(stages.len() - 1),
+                (true, None, None) => return Err(format!("layer {} has no parent", node.name).into()),
+            };
+
+            let source = match (node.kind.has_source(), &node.source) {
+                (true, Some(name)) => Some(find(&stages, name)?),
+                (false, None) => None,
+                (true, None) => return Err(format!("layer {} needs a source", node.name).into()),
+                (false, Some(_)) => return Err(format!("layer {} can't have a source", node.name).into()),
+            };
+
+            let mut layer = Box::new(Layer {
+                data: LayerData {
+                    parent: parent.map(|i| stages[i].layer.clone()),
+                    rand: SimpleRandom::new(node.salt, world_seed),
+                },
+                layer: node.kind.create(source.map(|i| stages[i].layer.clone())),
+            });
+
+            match node.cache {
+                Some(0) => return Err(format!("cache of layer {} must hold at least one tile", node.name).into()),
+                Some(capacity) => {
+                    layer = Box::new(Layer {
+                        data: LayerData {
+                            parent: Some(layer),
+                            rand: SimpleRandom::new(node.salt, world_seed),
+                        },
+                        layer: Box::new(LayerCache::new(capacity)),
+                    })
+                }
+                None => (),
+            }
+
+            zooms.push(parent.map_or(1, |i| zooms[i]) * node.kind.zoom());
+            let biomes = node.kind == LayerKind::BiomeType || parent.filter(|&i| stages[i].biomes).is_some();
+
+            stages.push(LayerStage {
+                name: node.name.clone(),
+                kind: node.kind,
+                scale: 0,
+                biomes,
+                layer,
+            });
+        }
+
+        let biomes = find(&stages, &self.biomes)?;
+        let zoomed = find(&stages, &self.zoomed)?;
+
+        for (stage, zoom) in stages.iter_mut().zip(&zooms) {
+            stage.scale = (zooms[zoomed] / zoom).max(1);
+        }
+
+        Ok(LayerGraph { stages, biomes, zoomed })
+    }
+}
+
+impl LayerGraph {
+    pub fn stages(&self) -> &[LayerStage] {
+        &self.stages
+    }
+
+    pub fn stages_mut(&mut self) -> &mut [LayerStage] {
+        &mut self.stages
+    }
+
+    pub fn stage(&mut self, name: &str) -> Option<&mut LayerStage> {
+        self.stages.iter_mut().find(|s| s.name == name)
+    }
+
+    /**
+     * Result of a stage on an area given in cells of the stage
+     */
+    pub fn generate(&mut self, name: &str, x: isize, y: isize, width: usize, height: usize) -> Option<LayerResult> {
+        self.stage(name).map(|s| s.layer.generate(x, y, width, height))
+    }
+
+    /**
+     * Biome layers at a 4 blocks and at a 1 block resolution
+     */
+    pub fn into_generators(mut self) -> (Box<Layer>, Box<Layer>) {
+        let zoomed = self.stages[self.zoomed].layer.clone();
+
+        (self.stages.swap_remove(self.biomes).layer, zoomed)
+    }
+}
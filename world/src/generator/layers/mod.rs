@@ -6,6 +6,8 @@ mod biome;
 mod smooth;
 mod cleaner;
 mod river;
+mod config;
+mod cache;
 
 pub use island::*;
 pub use zoom::*;
@@ -15,6 +17,8 @@ pub use biome::*;
 pub use smooth::*;
 pub use cleaner::*;
 pub use river::*;
+pub use config::*;
+pub use cache::*;
 
 use crate::generator::SimpleRandom;
 use crate::BiomeType;
@@ -60,7 +64,7 @@ impl LayerResult {
     }
 }
 
-pub trait LayerImpl {
+pub trait LayerImpl: Send {
     fn generate(&self, data: &mut LayerData, x: isize, y: isize, result: &mut LayerResult);
 
     fn clone_layer(&self) -> Box<dyn LayerImpl>;
@@ -79,82 +83,7 @@ pub struct Layer {
 }
 
 impl Layer {
-    pub fn create_generator(world_seed: isize) -> (Box<Layer>, Box<Layer>) {
-        let generator = |seed| SimpleRandom::new(seed, world_seed);
-        let layer = |parent, seed, layer: Box<dyn LayerImpl>| Some(Box::new(Layer {
-            data: LayerData {
-                parent,
-                rand: generator(seed),
-            },
-            layer,
-        }));
-
-        let mut lay = layer(None, 101, Box::new(LayerIsland::new()));
-
-        lay = layer(lay, 38127, Box::new(LayerZoom::new_fuzzy()));
-        lay = layer(lay, 3919, Box::new(LayerAddIsland::new()));
-        lay = layer(lay, 38127, Box::new(LayerZoom::new()));
-
-        lay = layer(lay, 39319, Box::new(LayerAddIsland::new()));
-        lay = layer(lay, 399, Box::new(LayerAddIsland::new()));
-        lay = layer(lay, 63119, Box::new(LayerAddIsland::new()));
-
-        lay = layer(lay, 3821, Box::new(LayerTooMuchOceans::new()));
-        
-        lay = layer(lay, 381, Box::new(LayerBiomeGroup::new()));
-        lay = layer(lay, 38127, Box::new(LayerZoom::new()));
-
-        let mut alter = layer(lay.clone(), 9272, Box::new(LayerCleaner::new()));
-        alter = layer(alter, 812, Box::new(LayerZoom::new()));
-        alter = layer(alter, 898, Box::new(LayerZoom::new()));
-
-        lay = layer(lay, 38138, Box::new(LayerBiomeType::new()));
-        lay = layer(lay, 382, Box::new(LayerDeepOcean::new()));
-
-
-        lay = layer(lay, 38131, Box::new(LayerZoom::new()));
-
-        lay = layer(lay, 38131, Box::new(LayerZoom::new()));
-        lay = layer(lay, 9833, Box::new(LayerAddIsland::new()));
-
-        lay = layer(lay, 3881, Box::new(LayerSmooth::new()));
-
-        let mut river = None;
-
-        for i in 0..4
-        {
-            lay = layer(lay, i * 37, Box::new(LayerZoom::new()));
-
-            if i == 0
-            {
-                lay = layer(lay, i * 3913, Box::new(LayerAddIsland::new()));
-                lay = layer(lay, 38133, Box::new(LayerBiomeHills::new(alter.clone().unwrap())));
-            }
-
-            if i == 1
-            {
-                river = layer(lay.clone(), 9282, Box::new(LayerRiver::new()));
-                river = layer(river, 3882, Box::new(LayerSmooth::new()));
-
-                lay = layer(lay, 28138, Box::new(LayerBiomeEdge::new()));
-            }
-        }
-
-        lay = layer(lay, 3981, Box::new(LayerSmooth::new()));
-        lay = layer(lay, 29282, Box::new(LayerRiverApply::new(river.unwrap())));
-
-        let mut zoomed = layer(lay.clone(), 9128, Box::new(LayerZoomVoronoi::new()));
-
-        for _ in 0..5
-        {
-            zoomed = layer(zoomed, 3981 + 37, Box::new(LayerSmooth::new()));
-            zoomed = layer(zoomed, 3981 + 37 * 2, Box::new(LayerSmooth::new()));
-        }
-
-        (lay.unwrap(), zoomed.unwrap())
-    }
-
-    pub fn init_seed(&mut self, seed: isize) {
+    pub fn init_seed(&mut self, seed: i64) {
         if let Some(parent) = &mut self.data.parent {
             parent.init_seed(seed);
         }
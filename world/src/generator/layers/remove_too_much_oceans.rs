@@ -71,7 +71,7 @@ impl LayerImpl for LayerDeepOcean {
 
 
                 if neighbours.iter().filter(|v| **v == 0).count() > 3 {
-                    BiomeType::DeepOcean as isize
+                    BiomeType::DeepOcean.id()
                 } else {
                     0
                 }
@@ -40,7 +40,7 @@ impl LayerImpl for LayerRiver {
             *v = if s == a && s == b && s == c && s == d {
                -1 
             } else {
-                BiomeType::River as isize
+                BiomeType::River.id()
             };
         })
     }
@@ -80,7 +80,7 @@ impl LayerImpl for LayerRiverApply {
             let a = *parent.at(dx, dy);
             let b = *sentry.at(dx , dy);
 
-            *v = if !is_ocean(a) && b == BiomeType::River as isize {
+            *v = if !is_ocean(a) && b == BiomeType::River.id() {
                 b
             } else {
                 a
@@ -1,10 +1,22 @@
+mod caves;
 mod column;
+mod flat;
 mod generator;
+mod noise_config;
+mod pipeline;
+mod seeds;
 mod simple_random;
+mod terrain;
 
 pub mod layers;
 pub mod decorators;
 
 pub use generator::*;
+pub use caves::*;
 pub use column::*;
+pub use flat::*;
+pub use noise_config::*;
+pub use pipeline::*;
+pub use seeds::*;
 pub use simple_random::*;
+pub use terrain::*;
@@ -0,0 +1,39 @@
+use perlin::{Billow, DomainWarp, NoiseSource, Octaves, PerlinNoise, Ridged, Scale, SimplexNoise};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+/**
+ * Composition of the noise sources of the `perlin` crate, as written in the terrain settings
+ */
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoiseConfig {
+    Perlin,
+    Simplex,
+    Ridged { source: Box<NoiseConfig>, octaves: usize },
+    Billow { source: Box<NoiseConfig>, octaves: usize },
+    /** `source` sampled at coordinates moved by `warp`, up to `strength` */
+    Warp { source: Box<NoiseConfig>, warp: Box<NoiseConfig>, strength: f32 },
+    Scale { source: Box<NoiseConfig>, frequency: f32, amplitude: f32 },
+}
+
+impl NoiseConfig {
+    /**
+     * Build the sources, their permutations are drawn from `rng` in the order of the config
+     */
+    pub fn build(&self, rng: &mut StdRng) -> Box<dyn NoiseSource> {
+        match self {
+            NoiseConfig::Perlin => Box::new(PerlinNoise::new(rng)),
+            NoiseConfig::Simplex => Box::new(SimplexNoise::new(rng)),
+            NoiseConfig::Ridged { source, octaves } => Box::new(Ridged::new(source.build(rng), Octaves::new(*octaves))),
+            NoiseConfig::Billow { source, octaves } => Box::new(Billow::new(source.build(rng), Octaves::new(*octaves))),
+            NoiseConfig::Warp { source, warp, strength } => {
+                let source = source.build(rng);
+                Box::new(DomainWarp::new(source, warp.build(rng), *strength))
+            }
+            NoiseConfig::Scale { source, frequency, amplitude } => {
+                Box::new(Scale::new(source.build(rng), *frequency, *amplitude))
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use nalgebra::{Vector2, Vector3};
+use rayon::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::sync::{Arc, Mutex};
+
+use crate::generator::decorators::{decorator_random, DecorationContext, DecorationWrite};
+use crate::generator::{terrain_factory, GeneratorSettings, TerrainFactory, TerrainGenerator};
+use crate::{world_to_chunk, Chunk};
+
+/**
+ * Chunks around (x, z) in canonical order: rows of increasing z, each of increasing x
+ */
+fn neighbourhood(x: i32, z: i32, radius: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-radius..=radius).flat_map(move |dz| (-radius..=radius).map(move |dx| (x + dx, z + dz)))
+}
+
+/**
+ * Chunks within `radius` of any of the given chunks, without duplicates
+ */
+fn neighbourhoods(coords: &[(i32, i32)], radius: i32) -> Vec<(i32, i32)> {
+    let mut seen = HashSet::new();
+
+    coords
+        .iter()
+        .flat_map(|&(x, z)| neighbourhood(x, z, radius))
+        .filter(|c| seen.insert(*c))
+        .collect()
+}
+
+type GeneratorPool = Mutex<Vec<Box<dyn TerrainGenerator>>>;
+
+/**
+ * Terrain generator taken from the pool of the pipeline by a parallel split, and given back once
+ * the split is done so that its caches are kept for the next chunks
+ */
+struct PooledGenerator<'a> {
+    pool: &'a GeneratorPool,
+    generator: Result<Box<dyn TerrainGenerator>, String>,
+}
+
+impl<'a> PooledGenerator<'a> {
+    fn take(pool: &'a GeneratorPool, factory: TerrainFactory, seed: i64, settings: &GeneratorSettings) -> PooledGenerator<'a> {
+        let generator = match pool.lock().unwrap().pop() {
+            Some(generator) => Ok(generator),
+            None => factory(seed, settings).map_err(|e| e.to_string()),
+        };
+
+        PooledGenerator { pool, generator }
+    }
+
+    fn generate_xz(&mut self, x: i32, z: i32) -> Result<Chunk, String> {
+        match &mut self.generator {
+            Ok(generator) => Ok(generator.generate_xz(x, z)),
+            Err(e) => Err(e.clone()),
+        }
+    }
+
+    fn carve(&mut self, surfaces: &HashMap<(i32, i32), Arc<Chunk>>, x: i32, z: i32) -> Result<Chunk, String> {
+        let mut chunk = (*surfaces[&(x, z)]).clone();
+
+        match &mut self.generator {
+            Ok(generator) => generator.carve(&mut chunk, surfaces),
+            Err(e) => return Err(e.clone()),
+        }
+
+        Ok(chunk)
+    }
+}
+
+impl Drop for PooledGenerator<'_> {
+    fn drop(&mut self) {
+        if let Ok(generator) = mem::replace(&mut self.generator, Err(String::new())) {
+            self.pool.lock().unwrap().push(generator);
+        }
+    }
+}
+
+/**
+ * Staged chunk generation:
+ *  - terrain and surface: only depend on the chunk itself
+ *  - caves: carved with the surface of the 3x3 neighbourhood of the chunk, to stay away from water
+ *  - decoration: runs the decorators of the biomes of a chunk over the carved chunks of its 3x3
+ *    neighbourhood, writes are buffered instead of being applied
+ *  - finalize: applies to the carved chunk the writes of the 9 decorations around it,
+ *    in canonical order
+ *
+ * A finalized chunk therefore only depends on the seed and its coordinates, whatever the
+ * chunks generated before it and their order. Intermediate stages are cached, as neighbouring
+ * chunks share them
+ */
+pub struct ChunkPipeline {
+    seed: i64,
+    generator: TerrainFactory,
+    settings: GeneratorSettings,
+    decorated: bool,
+    generators: GeneratorPool,
+    surfaces: HashMap<(i32, i32), Arc<Chunk>>,
+    carved: HashMap<(i32, i32), Arc<Chunk>>,
+    decorations: HashMap<(i32, i32), Arc<Vec<DecorationWrite>>>,
+}
+
+impl ChunkPipeline {
+    /**
+     * Pipeline of the terrain generator registered with the given name, the settings are checked
+     * by creating a first generator. Generators are kept between generations, one per worker at most
+     */
+    pub fn new(seed: i64, generator: &str, settings: &GeneratorSettings) -> Result<ChunkPipeline, Box<dyn std::error::Error>> {
+        let generator = terrain_factory(generator)?;
+        let first = generator(seed, settings)?;
+        let decorated = first.decorated();
+
+        Ok(ChunkPipeline {
+            seed,
+            generator,
+            settings: settings.clone(),
+            decorated,
+            generators: Mutex::new(vec![first]),
+            surfaces: HashMap::new(),
+            carved: HashMap::new(),
+            decorations: HashMap::new(),
+        })
+    }
+
+    /**
+     * Generate the given chunks, each stage running in parallel on the current rayon pool
+     */
+    pub fn generate(&mut self, coords: &[(i32, i32)]) -> Result<Vec<Chunk>, Box<dyn std::error::Error>> {
+        let seed = self.seed;
+        let generator = self.generator;
+        let settings = &self.settings;
+        let generators = &self.generators;
+        let decorated = self.decorated;
+
+        let missing: Vec<_> = neighbourhoods(coords, 3)
+            .into_iter()
+            .filter(|c| !self.surfaces.contains_key(c))
+            .collect();
+
+        let surfaces: Result<Vec<Chunk>, String> = missing
+            .par_iter()
+            .map_init(
+                || PooledGenerator::take(generators, generator, seed, settings),
+                |generator, &(x, z)| generator.generate_xz(x, z),
+            )
+            .collect();
+
+        for chunk in surfaces? {
+            self.surfaces.insert((chunk.coords().x, chunk.coords().y), Arc::new(chunk));
+        }
+
+        let missing: Vec<_> = neighbourhoods(coords, 2)
+            .into_iter()
+            .filter(|c| !self.carved.contains_key(c))
+            .collect();
+
+        let carved: Result<Vec<Chunk>, String> = {
+            let surfaces = &self.surfaces;
+
+            missing
+                .par_iter()
+                .map_init(
+                    || PooledGenerator::take(generators, generator, seed, settings),
+                    |generator, &(x, z)| generator.carve(surfaces, x, z),
+                )
+                .collect()
+        };
+
+        for chunk in carved? {
+            self.carved.insert((chunk.coords().x, chunk.coords().y), Arc::new(chunk));
+        }
+
+        let missing: Vec<_> = neighbourhoods(coords, 1)
+            .into_iter()
+            .filter(|c| !self.decorations.contains_key(c))
+            .collect();
+
+        let decorations: Vec<_> = {
+            let carved = &self.carved;
+
+            missing
+                .par_iter()
+                .map(|&(x, z)| match decorated {
+                    true => ChunkPipeline::decorate(seed, carved, x, z),
+                    false => Vec::new(),
+                })
+                .collect()
+        };
+
+        for (coords, writes) in missing.into_iter().zip(decorations) {
+            self.decorations.insert(coords, Arc::new(writes));
+        }
+
+        let pipeline = &*self;
+
+        Ok(coords.par_iter().map(|&(x, z)| pipeline.finalize(x, z)).collect())
+    }
+
+    fn decorate(seed: i64, carved: &HashMap<(i32, i32), Arc<Chunk>>, x: i32, z: i32) -> Vec<DecorationWrite> {
+        let position = carved[&(x, z)].position();
+        let mut context = DecorationContext::new(x, z, carved);
+
+        for biome in context.biomes() {
+            context.set_biome(biome);
+
+            for (i, decorator) in biome.decorators().iter().enumerate() {
+                let mut random = decorator_random(seed, Vector2::new(x, z), biome, i);
+                decorator.decorate(&mut context, &mut random, Vector3::new(position.x, 0, position.y));
+            }
+        }
+
+        context.into_writes()
+    }
+
+    fn finalize(&self, x: i32, z: i32) -> Chunk {
+        let mut chunk = (*self.carved[&(x, z)]).clone();
+
+        for coords in neighbourhood(x, z, 1) {
+            for (position, block) in self.decorations[&coords].iter() {
+                if world_to_chunk(*position) == (x, z) {
+                    chunk.set_block(position.x, position.y, position.z, *block);
+                }
+            }
+        }
+
+        chunk.set_decorated();
+
+        // generated chunks are only stored once modified, until then they are generated again
+        chunk.set_saved();
+        chunk
+    }
+
+    /**
+     * Drop the cached stages that can't be needed by a chunk next to the loaded ones:
+     * a stage is kept while some chunk depending on it is loaded and another one isn't
+     */
+    pub fn evict(&mut self, loaded: &HashSet<(i32, i32)>) {
+        let needed = |x: i32, z: i32, radius: i32| {
+            let mut any_loaded = false;
+            let mut any_missing = false;
+
+            for c in neighbourhood(x, z, radius) {
+                if loaded.contains(&c) {
+                    any_loaded = true;
+                } else {
+                    any_missing = true;
+                }
+            }
+
+            any_loaded && any_missing
+        };
+
+        self.surfaces.retain(|&(x, z), _| needed(x, z, 3));
+        self.carved.retain(|&(x, z), _| needed(x, z, 2));
+        self.decorations.retain(|&(x, z), _| needed(x, z, 1));
+    }
+}
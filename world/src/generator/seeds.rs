@@ -0,0 +1,32 @@
+/** Surface blocks of the columns of a chunk */
+pub const SURFACE_FEATURE: u64 = 0x5355_5246;
+/** Noises of the cave carver, the chunk coordinates are always (0, 0) */
+pub const CAVE_FEATURE: u64 = 0x4341_5645;
+/** The i-th decorator of a biome run on a chunk uses `DECORATOR_FEATURE + (biome id << 8) + i` */
+pub const DECORATOR_FEATURE: u64 = 0x4445_4300_0000;
+
+/**
+ * splitmix64 finalizer
+ */
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/**
+ * Seed of the random stream of a feature (see the `*_FEATURE` constants) in the given chunk.
+ * Every input is mixed in turn, so that close inputs (neighbouring chunks, world seeds only
+ * differing in their high bits) give unrelated streams
+ */
+pub fn feature_seed(world_seed: i64, feature: u64, x: i32, z: i32) -> u64 {
+    const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    let mut seed = mix64(world_seed as u64);
+
+    for value in &[feature, x as u32 as u64, z as u32 as u64] {
+        seed = mix64(seed.wrapping_add(GAMMA) ^ value);
+    }
+
+    seed
+}
@@ -1,19 +1,23 @@
 use std::num::Wrapping;
 
-const RNG_MULT: Wrapping<isize> = Wrapping(3823478372547234723);
-const RNG_ADD: Wrapping<isize> = Wrapping(1442695040888963407);
+const RNG_MULT: Wrapping<i64> = Wrapping(3823478372547234723);
+const RNG_ADD: Wrapping<i64> = Wrapping(1442695040888963407);
 
+/**
+ * Random generator of the biome layers. Its state is always 64 bits wide, so that a seed gives
+ * the same values on every target
+ */
 #[derive(Clone)]
 pub struct SimpleRandom {
     // wrapping are used to prevent integer overflow checks
 
-    seed: Wrapping<isize>,
-    gen_seed: Wrapping<isize>,
-    local_seed: Wrapping<isize>,
+    seed: Wrapping<i64>,
+    gen_seed: Wrapping<i64>,
+    local_seed: Wrapping<i64>,
 }
 
 impl SimpleRandom {
-    pub fn new(seed: isize, world_seed: isize) -> SimpleRandom {
+    pub fn new(seed: i64, world_seed: i64) -> SimpleRandom {
         let mut res = SimpleRandom {
             seed: Wrapping(seed),
             gen_seed: Wrapping(0),
@@ -24,7 +28,7 @@ impl SimpleRandom {
         res
     }
 
-    pub fn init_world(&mut self, seed: isize) {
+    pub fn init_world(&mut self, seed: i64) {
         self.gen_seed = Wrapping(seed);
 
         for _ in 0..3 {
@@ -36,8 +40,8 @@ impl SimpleRandom {
     pub fn init_local(&mut self, x: isize, z: isize) {
         self.local_seed = self.gen_seed;
 
-        let x = Wrapping(x);
-        let z = Wrapping(z);
+        let x = Wrapping(x as i64);
+        let z = Wrapping(z as i64);
 
         for _ in 0..2 {
             self.local_seed *= self.local_seed * RNG_MULT + RNG_ADD;
@@ -48,6 +52,7 @@ impl SimpleRandom {
     }
 
     pub fn next(&mut self, max: isize) -> isize {
+        let max = max as i64;
         let result = match (self.local_seed >> 24).0 % max {
             m if m < 0 => m + max,
             m => m,
@@ -56,7 +61,7 @@ impl SimpleRandom {
         self.local_seed *= self.local_seed * RNG_MULT + RNG_ADD;
         self.local_seed += self.gen_seed;
 
-        result
+        result as isize
     }
 
     pub fn cond(&mut self, diff: isize) -> bool {
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::generator::layers::LayerConfig;
+use crate::generator::{ChunkGenerator, FlatGenerator, FlatPreset, TerrainSettings};
+use crate::Chunk;
+
+/**
+ * Generator of the chunks before their decoration. Each generation thread has its own instance,
+ * and a chunk must only depend on the seed and its coordinates (see `ChunkPipeline`)
+ */
+pub trait TerrainGenerator: Send {
+    fn generate_xz(&mut self, x: i32, z: i32) -> Chunk;
+
+    /**
+     * Stage run on a generated chunk once the chunks around it are generated too, e.g. to carve
+     * caves away from the water of the neighbouring chunks. `neighbourhood` holds the 3x3
+     * chunks around it, as returned by `generate_xz`
+     */
+    fn carve(&mut self, _chunk: &mut Chunk, _neighbourhood: &HashMap<(i32, i32), Arc<Chunk>>) {}
+
+    /**
+     * Whether the decorators of the biomes run on the generated chunks
+     */
+    fn decorated(&self) -> bool {
+        true
+    }
+}
+
+/**
+ * Parameters of the generators of a world, stored in its metadata. Each generator reads its own
+ * section
+ */
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GeneratorSettings {
+    /** Shape of the terrain of the `layered` generator */
+    #[serde(default)]
+    pub terrain: TerrainSettings,
+    /** Layout of the `flat` generator */
+    #[serde(default)]
+    pub flat: FlatPreset,
+}
+
+impl GeneratorSettings {
+    /**
+     * Check the section read by the given generator, without creating it. Only the sections of
+     * the built-in generators can be checked
+     */
+    pub fn check(&self, generator: &str) -> Result<(), Box<dyn std::error::Error>> {
+        match generator {
+            "layered" => {
+                LayerConfig::load(&self.terrain.layers)?.build(0)?;
+            }
+            "flat" => {
+                FlatGenerator::new(&self.flat)?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Create the generator of a world from its seed and settings, failing on invalid settings
+ */
+pub type TerrainFactory = fn(i64, &GeneratorSettings) -> Result<Box<dyn TerrainGenerator>, Box<dyn std::error::Error>>;
+
+/**
+ * Name of the generator of new worlds
+ */
+pub const DEFAULT_GENERATOR: &str = "layered";
+
+lazy_static! {
+    static ref GENERATORS: RwLock<Vec<(String, TerrainFactory)>> = RwLock::new(vec![
+        ("layered".to_string(), |seed, settings| Ok(Box::new(ChunkGenerator::with_settings(seed, &settings.terrain)?))),
+        ("flat".to_string(), |_, settings| Ok(Box::new(FlatGenerator::new(&settings.flat)?))),
+    ]);
+}
+
+/**
+ * Make a generator available to worlds, under the given name
+ */
+pub fn register_terrain_generator(name: &str, factory: TerrainFactory) -> Result<(), Box<dyn std::error::Error>> {
+    let mut generators = GENERATORS.write().unwrap();
+
+    if generators.iter().any(|(n, _)| n == name) {
+        return Err(format!("terrain generator {} is already registered", name).into());
+    }
+
+    generators.push((name.to_string(), factory));
+    Ok(())
+}
+
+pub fn terrain_factory(name: &str) -> Result<TerrainFactory, Box<dyn std::error::Error>> {
+    let generators = GENERATORS.read().unwrap();
+
+    match generators.iter().find(|(n, _)| n == name) {
+        Some((_, factory)) => Ok(*factory),
+        None => Err(format!(
+            "unknown terrain generator {} (available: {})",
+            name,
+            terrain_generators().join(", ")
+        )
+        .into()),
+    }
+}
+
+/**
+ * Names of the registered generators, in registration order
+ */
+pub fn terrain_generators() -> Vec<String> {
+    GENERATORS.read().unwrap().iter().map(|(n, _)| n.clone()).collect()
+}
+
+impl TerrainGenerator for ChunkGenerator {
+    fn generate_xz(&mut self, x: i32, z: i32) -> Chunk {
+        ChunkGenerator::generate_xz(self, x, z)
+    }
+
+    fn carve(&mut self, chunk: &mut Chunk, neighbourhood: &HashMap<(i32, i32), Arc<Chunk>>) {
+        ChunkGenerator::carve(self, chunk, neighbourhood)
+    }
+}
@@ -1,5 +1,3 @@
-#![feature(get_mut_unchecked)]
-
 #[macro_use]
 extern crate lazy_static;
 
@@ -7,12 +5,18 @@ mod aabb;
 mod biome;
 mod block;
 mod chunk;
+mod chunk_section;
 mod chunk_manager;
+mod chunk_queue;
+mod region;
+mod storage;
 mod player;
 mod world;
+mod world_metadata;
 mod chunk_mesh;
 mod block_renderer;
 mod block_config;
+mod biome_config;
 
 pub mod generator;
 
@@ -20,12 +24,18 @@ pub use aabb::*;
 pub use biome::*;
 pub use block::*;
 pub use chunk::*;
+pub use chunk_section::*;
 pub use chunk_manager::*;
+pub use chunk_queue::*;
+pub use region::*;
+pub use storage::*;
 pub use player::*;
 pub use world::*;
+pub use world_metadata::*;
 pub use chunk_mesh::*;
 pub use block_renderer::*;
 pub use block_config::*;
+pub use biome_config::*;
 
 pub const SEA_LEVEL: i32 = 63;
 pub const MAX_HEIGHT: i32 = 256;
@@ -1,6 +1,6 @@
 use crate::{ivec_to_f, worldf_to_chunk, Block, BlockFace, World, AABB};
 use nalgebra::{Vector2, Vector3};
-use std::{collections::HashSet, rc::Rc};
+use std::{collections::HashSet, sync::Arc};
 
 const GRAVITY: f32 = 9.81;
 const WATER_GRAVITY: f32 = 1.5;
@@ -418,17 +418,10 @@ impl Player {
             }
         }
 
-        let curr_chunk = Vector2::new(cx, cz);
-        let mut new_chunks_vec: Vec<&Vector2<i32>> = new_chunks.iter().collect();
-
-        new_chunks_vec.sort_by(|a, b| {
-            let va = *a - curr_chunk;
-            let vb = *b - curr_chunk;
+        // chunk loads are prioritized by distance to the player
+        world.set_load_center(cx, cz);
 
-            (va.x * va.x + va.y * va.y).cmp(&(vb.x * vb.x + vb.y * vb.y))
-        });
-
-        for chunk in new_chunks_vec {
+        for chunk in &new_chunks {
             world.generate_chunk(chunk.x, chunk.y);
         }
 
@@ -472,13 +465,11 @@ impl Player {
 
         let mut modified = self.known_chunks
             .iter()
-            .filter(|v| {
-                if let Some(chunk) = world.chunk_mut(v.x, v.y) {
-                    let chunk = unsafe { Rc::get_mut_unchecked(chunk) };
-                    chunk.decorated() && (chunk.check_modified() || new.contains(&chunk.coords()))
-                } else {
-                    false
-                }
+            .filter(|v| match world.chunk_mut(v.x, v.y) {
+                // a chunk shared with a snapshot is only copied when its flag has to be cleared
+                Some(chunk) if chunk.decorated() && chunk.modified() => Arc::make_mut(chunk).check_modified(),
+                Some(chunk) => chunk.decorated() && new.contains(&chunk.coords()),
+                None => false,
             }).collect::<Vec<_>>();
 
         let (cx, cz) = worldf_to_chunk(position);
@@ -0,0 +1,465 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, prelude::*, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use crate::write_atomic;
+
+/**
+ * Region file format: groups REGION_SIZE * REGION_SIZE chunks in a single file
+ *  - two header slots, each one: magic (4 bytes), version (u16), reserved (u16),
+ *    generation (u32), crc32 of the generation and the entries (u32), then one entry per
+ *    chunk: offset in sectors (u32), compressed size in bytes (u32)
+ *  - chunk payloads: zlib compressed chunk data, each one starting on a sector boundary
+ *
+ * The valid header with the highest generation is used. An entry with a zero offset means the
+ * chunk isn't stored in the region. An entry pointing outside of the file only makes its own
+ * chunk unreadable.
+ * Version 1 files have a single header, without generation nor checksum.
+ * Stored chunks are never modified in place, see `RegionFile::commit`.
+ */
+pub const REGION_SIZE: i32 = 32;
+
+const REGION_MAGIC: &[u8; 4] = b"GLRG";
+const REGION_VERSION: u16 = 2;
+
+const CHUNK_COUNT: usize = (REGION_SIZE * REGION_SIZE) as usize;
+const ENTRY_SIZE: u64 = 8;
+const HEADER_SIZE: u64 = 16 + CHUNK_COUNT as u64 * ENTRY_SIZE;
+const V1_HEADER_SIZE: u64 = 8 + CHUNK_COUNT as u64 * ENTRY_SIZE;
+
+const SECTOR_BITS: u64 = 12;
+const SECTOR_SIZE: u64 = 1 << SECTOR_BITS;
+/** Sectors of each header slot */
+const HEADER_SECTORS: u32 = sector_count(HEADER_SIZE);
+/** First sector of the chunk payloads */
+const DATA_START: u32 = 2 * HEADER_SECTORS;
+const V1_DATA_START: u32 = sector_count(V1_HEADER_SIZE);
+
+fn invalid_data(message: &str) -> Box<dyn std::error::Error> {
+    Box::new(io::Error::new(io::ErrorKind::InvalidData, message.to_string()))
+}
+
+/**
+ * Coordinates of the region containing the given chunk
+ */
+pub fn region_coords(x: i32, z: i32) -> (i32, i32) {
+    (x >> 5, z >> 5)
+}
+
+#[derive(Copy, Clone, Default)]
+struct RegionEntry {
+    offset: u32,
+    size: u32,
+}
+
+impl RegionEntry {
+    /**
+     * Sectors used by the chunk, at least one
+     */
+    fn sectors(&self) -> u32 {
+        sector_count(self.size as u64).max(1)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.offset == 0
+    }
+}
+
+const fn sector_count(size: u64) -> u32 {
+    ((size + SECTOR_SIZE - 1) >> SECTOR_BITS) as u32
+}
+
+fn entry_index(x: i32, z: i32) -> usize {
+    ((x & (REGION_SIZE - 1)) + (z & (REGION_SIZE - 1)) * REGION_SIZE) as usize
+}
+
+fn read_u32(data: &[u8], p: usize) -> u32 {
+    u32::from_le_bytes([data[p], data[p + 1], data[p + 2], data[p + 3]])
+}
+
+fn read_entries(data: &[u8]) -> Vec<RegionEntry> {
+    (0..CHUNK_COUNT)
+        .map(|i| RegionEntry {
+            offset: read_u32(data, i * ENTRY_SIZE as usize),
+            size: read_u32(data, i * ENTRY_SIZE as usize + 4),
+        })
+        .collect()
+}
+
+fn checksum(generation: u32, entries: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+
+    hasher.update(&generation.to_le_bytes());
+    hasher.update(entries);
+    hasher.finalize()
+}
+
+/**
+ * Header slot of a version 2 file, padded to its sectors
+ */
+fn write_header(generation: u32, entries: &[RegionEntry]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(CHUNK_COUNT * ENTRY_SIZE as usize);
+
+    for entry in entries {
+        data.extend_from_slice(&entry.offset.to_le_bytes());
+        data.extend_from_slice(&entry.size.to_le_bytes());
+    }
+
+    let mut header = Vec::with_capacity((HEADER_SECTORS as u64 * SECTOR_SIZE) as usize);
+
+    header.extend_from_slice(REGION_MAGIC);
+    header.extend_from_slice(&REGION_VERSION.to_le_bytes());
+    header.extend_from_slice(&0u16.to_le_bytes());
+    header.extend_from_slice(&generation.to_le_bytes());
+    header.extend_from_slice(&checksum(generation, &data).to_le_bytes());
+    header.extend_from_slice(&data);
+    header.resize((HEADER_SECTORS as u64 * SECTOR_SIZE) as usize, 0);
+
+    header
+}
+
+/**
+ * Generation and entries of a version 2 header slot, if it is complete and valid
+ */
+fn parse_header(slot: &[u8]) -> Option<(u32, Vec<RegionEntry>)> {
+    if (slot.len() as u64) < HEADER_SIZE || &slot[0..4] != REGION_MAGIC {
+        return None;
+    }
+
+    let version = u16::from_le_bytes([slot[4], slot[5]]);
+    let generation = read_u32(slot, 8);
+    let entries = &slot[16..HEADER_SIZE as usize];
+
+    if version != REGION_VERSION || read_u32(slot, 12) != checksum(generation, entries) {
+        return None;
+    }
+
+    Some((generation, read_entries(entries)))
+}
+
+/**
+ * Region file opened for reading. Writes and deletions are kept in memory until `commit`
+ */
+pub struct RegionFile {
+    path: PathBuf,
+    file: Option<File>,
+    /** Format version of the file, 0 if there is no file yet */
+    version: u16,
+    generation: u32,
+    entries: Vec<RegionEntry>,
+    /** Entries pointing outside of the file */
+    corrupt: HashSet<usize>,
+    /** Compressed data of modified chunks, None for deleted ones */
+    pending: HashMap<usize, Option<Vec<u8>>>,
+}
+
+impl RegionFile {
+    /**
+     * Open a region file, a missing file is an empty region. Fails only if the header itself
+     * is unreadable
+     */
+    pub fn open(path: &Path) -> Result<RegionFile, Box<dyn std::error::Error>> {
+        let mut region = RegionFile {
+            path: path.to_path_buf(),
+            file: None,
+            version: 0,
+            generation: 0,
+            entries: vec![RegionEntry::default(); CHUNK_COUNT],
+            corrupt: HashSet::new(),
+            pending: HashMap::new(),
+        };
+
+        if !path.exists() {
+            return Ok(region);
+        }
+
+        let mut file = File::open(path)?;
+        let mut header = Vec::new();
+
+        (&mut file).take(DATA_START as u64 * SECTOR_SIZE).read_to_end(&mut header)?;
+
+        if header.len() < 8 {
+            return Err(invalid_data("truncated region header"));
+        }
+
+        if &header[0..4] != REGION_MAGIC {
+            return Err(invalid_data("invalid region magic"));
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+
+        let (generation, entries, data_start) = match version {
+            1 if header.len() as u64 >= V1_HEADER_SIZE => {
+                (0, read_entries(&header[8..V1_HEADER_SIZE as usize]), V1_DATA_START)
+            }
+            1 => return Err(invalid_data("truncated region header")),
+            REGION_VERSION => {
+                // the other slot may hold a header interrupted by a crash
+                let slot = (HEADER_SECTORS as u64 * SECTOR_SIZE) as usize;
+                let headers = [&header[..slot.min(header.len())], &header[slot.min(header.len())..]];
+
+                let (generation, entries) = headers.iter()
+                    .filter_map(|slot| parse_header(slot))
+                    .max_by_key(|(generation, _)| *generation)
+                    .ok_or_else(|| invalid_data("no valid region header"))?;
+
+                (generation, entries, DATA_START)
+            }
+            _ => return Err(invalid_data(&format!("unsupported region format version {}", version))),
+        };
+
+        let file_sectors = sector_count(file.metadata()?.len());
+
+        for (i, entry) in entries.into_iter().enumerate() {
+            if entry.is_empty() {
+                continue;
+            }
+
+            let end = entry.offset.checked_add(entry.sectors());
+
+            if entry.offset < data_start || end.filter(|&end| end <= file_sectors).is_none() {
+                region.corrupt.insert(i);
+            }
+
+            region.entries[i] = entry;
+        }
+
+        region.file = Some(file);
+        region.version = version;
+        region.generation = generation;
+
+        Ok(region)
+    }
+
+    pub fn contains(&self, x: i32, z: i32) -> bool {
+        match self.pending.get(&entry_index(x, z)) {
+            Some(data) => data.is_some(),
+            None => !self.entries[entry_index(x, z)].is_empty(),
+        }
+    }
+
+    fn read_stored(&mut self, index: usize, exact: bool) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let entry = self.entries[index];
+
+        let file = match &mut self.file {
+            Some(file) if !entry.is_empty() => file,
+            _ => return Ok(None),
+        };
+
+        if exact && self.corrupt.contains(&index) {
+            return Err(invalid_data("region entry out of bounds"));
+        }
+
+        let mut data = Vec::new();
+
+        file.seek(SeekFrom::Start(entry.offset as u64 * SECTOR_SIZE))?;
+        file.take(entry.size as u64).read_to_end(&mut data)?;
+
+        if exact && data.len() != entry.size as usize {
+            return Err(invalid_data("truncated region chunk"));
+        }
+
+        Ok(Some(data))
+    }
+
+    /**
+     * Compressed data of the given chunk, as stored (possibly truncated)
+     */
+    pub fn read_raw_chunk(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let index = entry_index(x, z);
+
+        match self.pending.get(&index) {
+            Some(data) => Ok(data.clone()),
+            None => self.read_stored(index, false),
+        }
+    }
+
+    /**
+     * Read and decompress the data of the given chunk
+     */
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let index = entry_index(x, z);
+
+        let compressed = match self.pending.get(&index) {
+            Some(data) => data.clone(),
+            None => self.read_stored(index, true)?,
+        };
+
+        match compressed {
+            Some(compressed) => {
+                let mut result = Vec::new();
+                ZlibDecoder::new(&compressed[..]).read_to_end(&mut result)?;
+
+                Ok(Some(result))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /**
+     * Compress the data of the given chunk, written by the next `commit`
+     */
+    pub fn write_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+
+        self.pending.insert(entry_index(x, z), Some(encoder.finish()?));
+
+        Ok(())
+    }
+
+    pub fn delete_chunk(&mut self, x: i32, z: i32) {
+        self.pending.insert(entry_index(x, z), None);
+    }
+
+    /**
+     * Write the pending modifications: the modified chunks go to sectors unused by the current
+     * header and are synced, then the next header is written to the other slot. A crash leaves
+     * either header valid, along with the chunks it points to, so the cost of a commit only
+     * depends on the modified chunks.
+     * New files and version 1 files are written whole instead
+     */
+    pub fn commit(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        if self.version != REGION_VERSION {
+            return self.rewrite();
+        }
+
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        let file_sectors = sector_count(file.metadata()?.len());
+
+        // sectors of the current header must survive until the next one is written
+        let mut used: Vec<(u32, u32)> = self.entries.iter()
+            .filter(|e| !e.is_empty())
+            .map(|e| (e.offset.max(DATA_START), e.offset.saturating_add(e.sectors()).min(file_sectors)))
+            .filter(|(start, end)| start < end)
+            .collect();
+
+        used.sort_unstable();
+
+        let mut entries = self.entries.clone();
+        let mut pending: Vec<_> = self.pending.drain().collect();
+
+        // written in entry order, so that the layout doesn't depend on the hash map
+        pending.sort_unstable_by_key(|(i, _)| *i);
+
+        for (i, data) in &pending {
+            let data = match data {
+                Some(data) => data,
+                None => {
+                    entries[*i] = RegionEntry::default();
+                    continue;
+                }
+            };
+
+            let entry = RegionEntry {
+                offset: 0,
+                size: data.len() as u32,
+            };
+
+            let sectors = entry.sectors();
+            let mut offset = DATA_START;
+
+            // first gap large enough
+            for &(start, end) in &used {
+                if start >= offset + sectors {
+                    break;
+                }
+
+                offset = offset.max(end);
+            }
+
+            used.push((offset, offset + sectors));
+            used.sort_unstable();
+
+            let mut padded = data.clone();
+            padded.resize((sectors as u64 * SECTOR_SIZE) as usize, 0);
+
+            file.seek(SeekFrom::Start(offset as u64 * SECTOR_SIZE))?;
+            file.write_all(&padded)?;
+
+            entries[*i] = RegionEntry { offset, ..entry };
+        }
+
+        file.sync_data()?;
+
+        let generation = self.generation.wrapping_add(1);
+        let slot = (generation % 2) as u64 * HEADER_SECTORS as u64 * SECTOR_SIZE;
+
+        file.seek(SeekFrom::Start(slot))?;
+        file.write_all(&write_header(generation, &entries))?;
+        file.sync_data()?;
+
+        // sectors after the last chunk of the new header are unused
+        let file_sectors = sector_count(file.metadata()?.len());
+        let end = entries.iter()
+            .filter(|e| !e.is_empty())
+            .map(|e| e.offset.saturating_add(e.sectors()).min(file_sectors))
+            .max()
+            .unwrap_or(0)
+            .max(DATA_START);
+
+        if end < file_sectors {
+            file.set_len(end as u64 * SECTOR_SIZE)?;
+        }
+
+        for (i, _) in &pending {
+            self.corrupt.remove(i);
+        }
+
+        self.file = Some(file);
+        self.entries = entries;
+        self.generation = generation;
+
+        Ok(())
+    }
+
+    /**
+     * Write the whole region with its pending modifications to a temporary file, and replace
+     * the previous file once it is synced. Unreadable chunks are kept as stored, to be
+     * quarantined when they are loaded
+     */
+    fn rewrite(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut body = Vec::new();
+        let mut entries = vec![RegionEntry::default(); CHUNK_COUNT];
+
+        for (i, entry) in entries.iter_mut().enumerate() {
+            let data = match self.pending.get(&i).cloned() {
+                Some(data) => data,
+                None => self.read_stored(i, false)?,
+            };
+
+            if let Some(data) = data {
+                *entry = RegionEntry {
+                    offset: DATA_START + sector_count(body.len() as u64),
+                    size: data.len() as u32,
+                };
+
+                body.extend_from_slice(&data);
+                body.resize(((entry.offset - DATA_START + entry.sectors()) as u64 * SECTOR_SIZE) as usize, 0);
+            }
+        }
+
+        // the second slot is left invalid, it receives the next header
+        let mut data = write_header(0, &entries);
+        data.resize((DATA_START as u64 * SECTOR_SIZE) as usize, 0);
+        data.extend_from_slice(&body);
+
+        write_atomic(&self.path, &data)?;
+
+        self.file = Some(File::open(&self.path)?);
+        self.version = REGION_VERSION;
+        self.generation = 0;
+        self.entries = entries;
+        self.corrupt.clear();
+        self.pending.clear();
+
+        Ok(())
+    }
+}
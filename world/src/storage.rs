@@ -0,0 +1,70 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, prelude::*};
+use std::path::Path;
+
+use fs2::FileExt;
+
+const SESSION_LOCK_FILE: &str = "session.lock";
+
+/**
+ * Write a file of the world folder: data is written to a temporary file next to it,
+ * synced to the disk, then renamed over the previous file. Readers (or a crashed process)
+ * see either the old or the new content, never a partial write
+ */
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    let name = path.file_name()
+        .and_then(|v| v.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?;
+
+    let temp = path.with_file_name(format!(".{}.tmp", name));
+
+    {
+        let mut file = File::create(&temp)?;
+
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+
+    fs::rename(&temp, path)?;
+
+    // make the rename itself durable (directories can't be opened on every platform)
+    if let Some(dir) = path.parent().and_then(|p| File::open(p).ok()) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(())
+}
+
+/**
+ * Exclusive lock of a world folder, held while the world is open.
+ * The lock is released by the OS when the process exits, even after a crash
+ */
+pub struct SessionLock {
+    _file: File,
+}
+
+impl SessionLock {
+    pub fn acquire(world_path: &Path) -> Result<SessionLock, Box<dyn std::error::Error>> {
+        fs::create_dir_all(world_path)?;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(world_path.join(SESSION_LOCK_FILE))?;
+
+        if file.try_lock_exclusive().is_err() {
+            return Err(Box::new(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!("world {} is already opened by another process", world_path.display()),
+            )));
+        }
+
+        // informative only: the OS lock is what prevents concurrent sessions
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+
+        Ok(SessionLock { _file: file })
+    }
+}
@@ -1,45 +1,65 @@
 use nalgebra::{Vector2, Vector3};
 
-use std::{collections::HashMap, rc::Rc, sync::mpsc, thread};
+use std::{collections::HashMap, path::Path, sync::{mpsc, Arc, Mutex, RwLock}, thread};
 
-use crate::{world_to_chunk, Block, Chunk, ChunkListener, ChunkManager, Player, BlockRenderer, BlockConfig, TextureList};
+use crate::{biomes_hash, generator::ChunkPipeline, world_to_chunk, Block, Chunk, ChunkError, ChunkListener, ChunkManager, ChunkQueue, SessionLock, Player, WorldMetadata, BlockRenderer, BlockConfig, TextureList};
 
-pub static mut WORLD: Option<Box<World>> = None;
+/**
+ * Create a world stored in the given folder, generated as described by its metadata, and start
+ * its chunk manager thread. The folder is locked until the world is dropped and the manager has
+ * saved its last chunks
+ */
+pub fn create_world(world_path: &str, metadata: &WorldMetadata) -> Result<Arc<RwLock<World>>, Box<dyn std::error::Error>> {
+    // chunks store the ids of their biomes, which depend on the definitions
+    if metadata.biomes != biomes_hash() {
+        return Err(format!("world {} was created with other biome definitions", world_path).into());
+    }
 
-pub fn create_main_world(world_path: &str, seed: isize, flat: bool) -> &'static mut Box<World> {
-    let (tx, rx) = mpsc::channel();
+    let seed = metadata.seed;
+    let pipeline = ChunkPipeline::new(seed, &metadata.generator, &metadata.generator_settings)?;
+    let session = SessionLock::acquire(Path::new(world_path))?;
 
-    unsafe {
-        WORLD = Some(Box::new(World::new(tx, seed)));
-    }
+    let queue = Arc::new(ChunkQueue::new());
+    let (errors, receiver) = mpsc::channel();
+
+    let world = Arc::new(RwLock::new(World::new(queue.clone(), receiver, seed)));
 
     let wp = world_path.to_string();
-    thread::spawn(move || ChunkManager::new(&wp, seed, flat, rx));
+    let handle = Arc::downgrade(&world);
 
-    main_world()
-}
+    let manager = thread::spawn(move || {
+        ChunkManager::new(handle, &wp, pipeline, queue, errors);
+        drop(session);
+    });
+    world.write().unwrap().manager = Some(manager);
 
-pub fn main_world() -> &'static mut Box<World> {
-    unsafe { WORLD.as_mut().unwrap() }
+    Ok(world)
 }
 
+/**
+ * Loaded chunks of a world. A world is shared between threads behind a `RwLock`:
+ * chunks are reference counted and copied on write, so that a snapshot of a chunk
+ * can be used (e.g. saved) without holding the lock
+ */
 pub struct World {
-    chunks: HashMap<Vector2<i32>, Rc<Chunk>>,
-    sender: mpsc::Sender<(bool, i32, i32)>,
-    seed: isize,
+    chunks: HashMap<Vector2<i32>, Arc<Chunk>>,
+    queue: Arc<ChunkQueue>,
+    errors: Mutex<mpsc::Receiver<ChunkError>>,
+    manager: Option<thread::JoinHandle<()>>,
+    seed: i64,
 
     pub textures: TextureList,
-    pub renderers: Vec<BlockRenderer>,
+    pub renderers: Arc<Vec<BlockRenderer>>,
 }
 
 impl World {
-    pub fn new(sender: mpsc::Sender<(bool, i32, i32)>, seed: isize) -> World {
+    pub fn new(queue: Arc<ChunkQueue>, errors: mpsc::Receiver<ChunkError>, seed: i64) -> World {
         let config: BlockConfig = serde_yaml::from_str(include_str!("block_data.yaml")).unwrap();
 
         let mut textures = config.init_texture_list();
         let mut renderers = Vec::new();
 
-        for i in 0..=Block::Mirror as u32 {
+        for i in 0..Block::count() {
             let block = Block::from_id(i);
 
             renderers.push(
@@ -50,17 +70,26 @@ impl World {
         World {
             chunks: HashMap::new(),
             seed,
-            sender,
-            renderers,
+            queue,
+            errors: Mutex::new(errors),
+            manager: None,
+            renderers: Arc::new(renderers),
             textures,
         }
     }
 
-    pub fn seed(&self) -> isize {
+    pub fn seed(&self) -> i64 {
         self.seed
     }
 
-    pub fn get_ref_chunks(&self) -> &HashMap<Vector2<i32>, Rc<Chunk>> {
+    /**
+     * Errors reported by the chunk manager since the last call
+     */
+    pub fn errors(&self) -> Vec<ChunkError> {
+        self.errors.lock().unwrap().try_iter().collect()
+    }
+
+    pub fn get_ref_chunks(&self) -> &HashMap<Vector2<i32>, Arc<Chunk>> {
         &self.chunks
     }
 
@@ -68,50 +97,65 @@ impl World {
         self.chunks.contains_key(&Vector2::new(x, z))
     }
 
-    pub fn chunk(&self, x: i32, z: i32) -> Option<&Rc<Chunk>> {
+    pub fn chunk(&self, x: i32, z: i32) -> Option<&Arc<Chunk>> {
         self.chunks.get(&Vector2::new(x, z))
     }
 
-    pub fn chunk_mut(&mut self, x: i32, z: i32) -> Option<&mut Rc<Chunk>> {
+    pub fn chunk_mut(&mut self, x: i32, z: i32) -> Option<&mut Arc<Chunk>> {
         self.chunks.get_mut(&Vector2::new(x, z))
     }
 
-    pub fn chunk_at(&self, position: Vector3<i32>) -> Option<&Rc<Chunk>> {
+    pub fn chunk_at(&self, position: Vector3<i32>) -> Option<&Arc<Chunk>> {
         let (x, z) = world_to_chunk(position);
 
         self.chunk(x, z)
     }
 
-    pub fn chunk_mut_at(&mut self, position: Vector3<i32>) -> Option<&mut Rc<Chunk>> {
+    pub fn chunk_mut_at(&mut self, position: Vector3<i32>) -> Option<&mut Arc<Chunk>> {
         let (x, z) = world_to_chunk(position);
 
         self.chunk_mut(x, z)
     }
 
-    pub fn add_chunk(&mut self, chunk: Rc<Chunk>) {
+    pub fn add_chunk(&mut self, chunk: Arc<Chunk>) {
         self.chunks.insert(chunk.coords(), chunk);
     }
 
-    pub fn remove_chunk(&mut self, x: i32, z: i32) {
-        self.chunks.remove(&Vector2::new(x, z));
+    pub fn remove_chunk(&mut self, x: i32, z: i32) -> Option<Arc<Chunk>> {
+        self.chunks.remove(&Vector2::new(x, z))
+    }
+
+    /**
+     * Set the chunk around which chunk loads are prioritized
+     */
+    pub fn set_load_center(&self, x: i32, z: i32) {
+        self.queue.set_center(x, z);
     }
 
     pub fn generate_chunk(&self, x: i32, z: i32) {
-        if let Some(c) = self.chunk(x, z) {
-            if c.decorated() {
-                return;
-            }
+        // the chunk is seen again before being unloaded
+        self.queue.cancel_unload(x, z);
+
+        if self.chunk_loaded(x, z) {
+            return;
         }
 
-        self.sender
-            .send((true, x, z))
-            .expect("error while sending load chunk request");
+        self.queue.load(x, z);
     }
 
     pub fn unload_chunk(&self, x: i32, z: i32) {
-        self.sender
-            .send((false, x, z))
-            .expect("error while sending load chunk request");
+        self.queue.unload(x, z);
+    }
+
+    /**
+     * Write every modified chunk to the world folder, blocking until done.
+     * The world lock is only held to send the request, as the chunk manager needs it
+     */
+    pub fn save(world: &RwLock<World>) {
+        let (tx, rx) = mpsc::channel();
+
+        world.read().unwrap().queue.flush(tx);
+        rx.recv().expect("error while waiting for world save");
     }
 
     pub fn highest_y(&self, x: i32, z: i32) -> i32 {
@@ -130,10 +174,12 @@ impl World {
         }
     }
 
-    fn update_chunk(&mut self, x: i32, y: i32) {
+    /**
+     * Mark the chunk for meshing again
+     */
+    pub fn update_chunk(&mut self, x: i32, y: i32) {
         if let Some(chunk) = self.chunk_mut(x, y) {
-            let chunk = unsafe { Rc::get_mut_unchecked(chunk) };
-            chunk.set_modified();
+            Arc::make_mut(chunk).set_modified();
         }
     }
 
@@ -147,7 +193,7 @@ impl World {
                 position.z - chunk_pos.y,
             );
 
-            let chunk = unsafe { Rc::get_mut_unchecked(chunk) };
+            let chunk = Arc::make_mut(chunk);
             chunk.set_block_at_chunk(position.x, position.y, position.z, block);
 
             let chunk_pos = chunk.coords();
@@ -183,10 +229,26 @@ impl World {
         &mut self,
         listener: &mut dyn ChunkListener,
         view_distance: usize,
+        position: Vector3<f32>,
     ) -> Player {
         let mut player = Player::new(view_distance);
-        player.set_position(self, listener, Vector3::new(0.0, 100.0, 0.0));
+        player.set_position(self, listener, position);
 
         player
     }
 }
+
+impl Drop for World {
+    fn drop(&mut self) {
+        // the chunk manager saves the remaining chunks and stops
+        let chunks = self.chunks.values().filter(|c| c.unsaved()).cloned().collect();
+        self.queue.close(chunks);
+
+        // unless the world is dropped by the manager itself, which then drains the queue
+        if let Some(manager) = self.manager.take() {
+            if manager.thread().id() != thread::current().id() {
+                let _ = manager.join();
+            }
+        }
+    }
+}
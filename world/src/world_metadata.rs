@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+use nalgebra::{Vector2, Vector3};
+
+use std::fs;
+use std::path::Path;
+
+use crate::generator::{GeneratorSettings, DEFAULT_GENERATOR};
+use crate::{biomes_hash, write_atomic};
+
+const METADATA_FILE: &str = "world.json";
+
+fn default_generator() -> String {
+    DEFAULT_GENERATOR.to_string()
+}
+
+/**
+ * World-level information, stored in the world folder next to the region files
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WorldMetadata {
+    pub seed: i64,
+    /** Name of the terrain generator, see `register_terrain_generator` */
+    #[serde(default = "default_generator")]
+    pub generator: String,
+    #[serde(default)]
+    pub generator_settings: GeneratorSettings,
+    /** Hash of the custom biome definitions of the world, see `biomes_hash` */
+    #[serde(default)]
+    pub biomes: Option<u32>,
+    pub spawn: Vector3<f32>,
+
+    pub player_position: Vector3<f32>,
+    pub player_rotation: Vector2<f32>,
+
+    pub sun_direction: Vector3<f32>,
+    pub light_cycle: f32,
+}
+
+impl WorldMetadata {
+    pub fn new(seed: i64, generator: &str) -> WorldMetadata {
+        let spawn = Vector3::new(0.0, 100.0, 0.0);
+
+        WorldMetadata {
+            seed,
+            generator: generator.to_string(),
+            generator_settings: GeneratorSettings::default(),
+            biomes: biomes_hash(),
+            spawn,
+            player_position: spawn,
+            player_rotation: Vector2::new(std::f32::consts::PI / 2.0, 0.0),
+            sun_direction: Vector3::new(0.1, -1.0, 0.1),
+            light_cycle: 0.0,
+        }
+    }
+
+    pub fn load(world_path: &str) -> Result<Option<WorldMetadata>, Box<dyn std::error::Error>> {
+        let path = Path::new(world_path).join(METADATA_FILE);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /**
+     * Load the metadata of an existing world, or save the metadata given by `new` for a new one
+     */
+    pub fn load_or_create(
+        world_path: &str,
+        new: impl FnOnce() -> Result<WorldMetadata, Box<dyn std::error::Error>>,
+    ) -> Result<WorldMetadata, Box<dyn std::error::Error>> {
+        match WorldMetadata::load(world_path)? {
+            Some(metadata) => Ok(metadata),
+            None => {
+                let metadata = new()?;
+                metadata.save(world_path)?;
+
+                Ok(metadata)
+            }
+        }
+    }
+
+    pub fn save(&self, world_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Path::new(world_path);
+
+        fs::create_dir_all(path)?;
+        write_atomic(&path.join(METADATA_FILE), serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
@@ -0,0 +1,102 @@
+use world::{create_world, BiomeConfig, BiomeType, Block, Chunk, WorldMetadata, BUILTIN_BIOMES};
+
+const DEFAULT_BIOMES: &str = include_str!("../src/biome_data.yaml");
+
+const CUSTOM_BIOME: &str = "
+    - name: RedDesert
+      shape: Low
+      color: [200, 60, 20]
+      temperature: 2.0
+      rainfall: 0.0
+      top_block: Gravel
+      decorators:
+          - { type: cactus, count: 5 }
+          - { type: set, name: warm_veins }
+";
+
+#[test]
+fn default_biomes_are_loaded() {
+    let registry = BiomeConfig::from_yaml(DEFAULT_BIOMES).unwrap().build().unwrap();
+
+    assert_eq!(registry.biomes.len(), BUILTIN_BIOMES.len());
+    assert_eq!(registry.groups.len(), 4);
+
+    for (biome, name) in registry.biomes.iter().zip(BUILTIN_BIOMES) {
+        assert_eq!(biome.name, *name);
+    }
+
+    assert_eq!(BiomeType::count(), BUILTIN_BIOMES.len() as isize);
+    assert_eq!(BiomeType::Desert.name(), "Desert");
+    assert_eq!(BiomeType::Desert.get_hills_version(), BiomeType::DesertHills);
+    assert!(BiomeType::DeepOcean.is_ocean());
+}
+
+#[test]
+fn biomes_can_be_appended() {
+    let yaml = format!("{}{}", DEFAULT_BIOMES, CUSTOM_BIOME);
+    let registry = BiomeConfig::from_yaml(&yaml).unwrap().build().unwrap();
+
+    let biome = registry.biomes.last().unwrap();
+
+    assert_eq!(registry.biomes.len(), BUILTIN_BIOMES.len() + 1);
+    assert_eq!(biome.top_block, Block::Gravel);
+    assert_eq!(biome.column_block, Block::Dirt);
+    assert_eq!(biome.hills.id(), BUILTIN_BIOMES.len() as isize);
+    assert_eq!(biome.decorators.len(), 8);
+}
+
+#[test]
+fn invalid_biomes_are_rejected() {
+    let invalid = [
+        // renamed built-in biome
+        DEFAULT_BIOMES.replacen("name: Ocean", "name: Sea", 1),
+        // unknown decorator set
+        format!("{}{}", DEFAULT_BIOMES, CUSTOM_BIOME.replace("warm_veins", "hot_veins")),
+        // unknown hills version
+        DEFAULT_BIOMES.replacen("hills: Hills", "hills: Peaks", 1),
+        // duplicated biome
+        format!("{}{}{}", DEFAULT_BIOMES, CUSTOM_BIOME, CUSTOM_BIOME),
+    ];
+
+    for yaml in &invalid {
+        assert!(BiomeConfig::from_yaml(yaml).unwrap().build().is_err());
+    }
+}
+
+#[test]
+fn unknown_biome_ids_fall_back_to_the_ocean() {
+    assert_eq!(BiomeType::from_id(BiomeType::count()), BiomeType::Ocean);
+    assert_eq!(BiomeType::from_id(300), BiomeType::Ocean);
+    assert_eq!(BiomeType::from_id(-1), BiomeType::Ocean);
+
+    let mut chunk = Chunk::new(0, 0);
+    *chunk.biome_at_mut(3, 4) = BiomeType::Desert;
+    chunk.set_block_at_chunk(3, 10, 4, Block::Sand);
+
+    // biome id of (3, 4) in the payload, followed by the grass colors, with the payload checksum
+    let mut data = chunk.to_bytes();
+    let offset = data.len() - 16 * 16 * 17 + 3 + 4 * 16;
+    data[offset] = 200;
+
+    let checksum = crc32fast::hash(&data[16..]);
+    data[12..16].copy_from_slice(&checksum.to_le_bytes());
+
+    let loaded = Chunk::from_bytes(0, 0, &data).unwrap();
+
+    assert_eq!(*loaded.biome_at(3, 4), BiomeType::Ocean);
+    assert_eq!(loaded.block_at_chunk(3, 10, 4), Block::Sand);
+}
+
+#[test]
+fn worlds_keep_their_biome_definitions() {
+    let path = std::env::temp_dir().join(format!("glopr-biome-definitions-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+
+    let mut metadata = WorldMetadata::new(0, "flat");
+    assert_eq!(metadata.biomes, None);
+
+    metadata.biomes = Some(1234);
+    assert!(create_world(path.to_str().unwrap(), &metadata).is_err());
+
+    let _ = std::fs::remove_dir_all(&path);
+}
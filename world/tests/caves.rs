@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use world::generator::CaveCarver;
+use world::{BiomeType, Block, Chunk, SEA_LEVEL};
+
+/** Ground of the chunks under water, a margin of 6 blocks is kept above caves */
+const WATER_FLOOR: i32 = 50;
+
+/**
+ * Dry land of dense caves, with water on both sides: the water of the chunks (-1, 0) and (1, 0)
+ * is right across the borders of the chunk (0, 0)
+ */
+fn water_around() -> (Chunk, HashMap<(i32, i32), Arc<Chunk>>) {
+    let mut neighbourhood = HashMap::new();
+
+    for cx in -1..=1 {
+        for cz in -1..=1 {
+            let mut chunk = Chunk::new(cx, cz);
+            let ground = if cz == 0 && cx != 0 { WATER_FLOOR } else { SEA_LEVEL + 8 };
+
+            for x in 0..16 {
+                for z in 0..16 {
+                    *chunk.biome_at_mut(x, z) = BiomeType::Moutains;
+
+                    for y in 0..=SEA_LEVEL.max(ground) {
+                        let block = if y <= ground { Block::Stone } else { Block::Water };
+                        chunk.set_block_at_chunk(x, y, z, block);
+                    }
+                }
+            }
+
+            neighbourhood.insert((cx, cz), Arc::new(chunk));
+        }
+    }
+
+    ((*neighbourhood[&(0, 0)]).clone(), neighbourhood)
+}
+
+/**
+ * Whether a block of the border columns was carved above the margin kept under the water
+ */
+fn opens_under_water(chunk: &Chunk) -> bool {
+    (0..16).any(|z| {
+        [0, 15]
+            .iter()
+            .any(|&x| (WATER_FLOOR - 6..=SEA_LEVEL).any(|y| chunk.block_at_chunk(x, y, z) == Block::Air))
+    })
+}
+
+#[test]
+fn caves_keep_away_from_water_across_chunk_borders() {
+    let mut opened = 0;
+
+    for seed in 0..32 {
+        let carver = CaveCarver::new(seed);
+        let (chunk, neighbourhood) = water_around();
+
+        // without its neighbours, the chunk doesn't know about their water
+        let mut alone = chunk.clone();
+        carver.carve(&mut alone, &HashMap::new());
+
+        if opens_under_water(&alone) {
+            opened += 1;
+        }
+
+        let mut carved = chunk;
+        carver.carve(&mut carved, &neighbourhood);
+
+        assert!(!opens_under_water(&carved), "seed {}: cave opened under the water of a neighbour", seed);
+    }
+
+    assert!(opened > 0, "no cave reached the chunk borders");
+}
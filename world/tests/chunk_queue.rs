@@ -0,0 +1,76 @@
+use std::fs;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use nalgebra::Vector3;
+
+use world::{create_world, Block, Chunk, ChunkQueue, RegionFile, SessionLock, WorldMetadata};
+
+#[test]
+fn closed_queue_serves_unloads_and_saves() {
+    let queue = ChunkQueue::new();
+
+    queue.load(0, 0);
+    queue.unload(1, 0);
+    queue.close(vec![Arc::new(Chunk::new(2, 0))]);
+
+    let batch = queue.pop().unwrap();
+
+    assert!(batch.loads.is_empty());
+    assert_eq!(batch.unloads, [(1, 0)]);
+    assert_eq!(batch.saves.len(), 1);
+    assert!(queue.pop().is_none());
+}
+
+#[test]
+fn far_loads_are_sorted() {
+    let queue = ChunkQueue::new();
+
+    queue.set_center(-1_000_000, 0);
+    queue.load(1_000_000, 0);
+    queue.load(0, 1_000_000);
+    queue.load(-999_999, 0);
+
+    let batch = queue.pop().unwrap();
+    assert_eq!(batch.loads, [(-999_999, 0), (0, 1_000_000), (1_000_000, 0)]);
+}
+
+#[test]
+fn dropped_world_saves_its_chunks() {
+    let path = std::env::temp_dir().join(format!("glopr-queue-drop-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(3, "flat")).unwrap();
+    world.read().unwrap().generate_chunk(0, 0);
+    world.read().unwrap().generate_chunk(1, 0);
+
+    let start = Instant::now();
+
+    while !world.read().unwrap().chunk_loaded(0, 0) || !world.read().unwrap().chunk_loaded(1, 0) {
+        assert!(start.elapsed() < Duration::from_secs(30), "chunk wasn't loaded");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    world.write().unwrap().set_block_at(Vector3::new(1, 10, 1), Block::Sand);
+    world.read().unwrap().unload_chunk(0, 0);
+
+    // neither saved nor unloaded yet
+    drop(world);
+
+    // the manager may hold the last reference, the folder is unlocked once it is done
+    while SessionLock::acquire(&path).is_err() {
+        assert!(start.elapsed() < Duration::from_secs(30), "world wasn't released");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut region = RegionFile::open(&path.join("r.0.0.rg")).unwrap();
+    let data = region.read_chunk(0, 0).unwrap().unwrap();
+
+    assert_eq!(Chunk::from_bytes(0, 0, &data).unwrap().block_at_chunk(1, 10, 1), Block::Sand);
+
+    // unmodified chunks are generated again instead of being stored
+    assert!(!region.contains(1, 0));
+
+    fs::remove_dir_all(&path).unwrap();
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use world::{create_world, Block, Chunk, ChunkError, RegionFile, World, WorldMetadata};
+
+const FIXTURES: &str = "tests/fixtures";
+const SEED: i64 = 42;
+
+/**
+ * Copy a fixture world to a fresh temporary folder, as the chunk manager modifies it
+ */
+fn fixture_world(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("glopr-recovery-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+
+    for entry in fs::read_dir(Path::new(FIXTURES).join(name)).unwrap() {
+        let entry = entry.unwrap();
+        fs::copy(entry.path(), path.join(entry.file_name())).unwrap();
+    }
+
+    path
+}
+
+fn load_chunk(world: &Arc<RwLock<World>>, x: i32, z: i32) -> Arc<Chunk> {
+    world.read().unwrap().generate_chunk(x, z);
+
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(30) {
+        if let Some(chunk) = world.read().unwrap().chunk(x, z) {
+            if chunk.decorated() {
+                return chunk.clone();
+            }
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    panic!("chunk ({}, {}) wasn't loaded", x, z);
+}
+
+/**
+ * Fixture chunks are filled with grass up to the sea level, while flat generation puts stone there
+ */
+fn is_regenerated(chunk: &Chunk) -> bool {
+    chunk.block_at_chunk(0, 1, 0) == Block::Stone
+}
+
+fn quarantined(error: &ChunkError, x: i32, z: i32) -> PathBuf {
+    match error {
+        ChunkError::Corrupted { x: ex, z: ez, quarantine: Some(path), .. } if (*ex, *ez) == (x, z) => path.clone(),
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn corrupted_chunk_is_quarantined_and_regenerated() {
+    let path = fixture_world("corrupted_chunk");
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    let chunk = load_chunk(&world, 0, 0);
+    assert!(is_regenerated(&chunk));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(quarantined(&errors[0], 0, 0).exists());
+
+    // the other chunk of the region is still readable
+    assert!(!is_regenerated(&load_chunk(&world, 1, 0)));
+
+    // the regenerated chunk is only stored once modified
+    World::save(&world);
+    assert!(!RegionFile::open(&path.join("r.0.0.rg")).unwrap().contains(0, 0));
+
+    world.write().unwrap().set_block_at_coords(1, 100, 1, Block::Sand);
+    World::save(&world);
+
+    let data = RegionFile::open(&path.join("r.0.0.rg")).unwrap().read_chunk(0, 0).unwrap().unwrap();
+    assert!(is_regenerated(&Chunk::from_bytes(0, 0, &data).unwrap()));
+}
+
+#[test]
+fn out_of_bounds_entry_only_quarantines_its_chunk() {
+    let path = fixture_world("corrupted_chunk");
+    let region = path.join("r.0.0.rg");
+
+    // entry of the chunk (0, 0): its end overflows
+    let mut data = fs::read(&region).unwrap();
+    data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&region, &data).unwrap();
+
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    assert!(is_regenerated(&load_chunk(&world, 0, 0)));
+    assert!(!is_regenerated(&load_chunk(&world, 1, 0)));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(quarantined(&errors[0], 0, 0).exists());
+    assert!(RegionFile::open(&region).unwrap().contains(1, 0));
+}
+
+#[test]
+fn truncated_chunk_is_quarantined_and_regenerated() {
+    let path = fixture_world("truncated_chunk");
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    assert!(is_regenerated(&load_chunk(&world, 0, 0)));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(quarantined(&errors[0], 0, 0).exists());
+    assert!(!RegionFile::open(&path.join("r.0.0.rg")).unwrap().contains(0, 0));
+}
+
+#[test]
+fn region_with_truncated_chunk_can_be_saved() {
+    let path = fixture_world("truncated_chunk");
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    load_chunk(&world, 1, 0);
+    world.write().unwrap().set_block_at_coords(17, 100, 1, Block::Sand);
+    World::save(&world);
+
+    assert!(world.read().unwrap().errors().is_empty());
+    assert!(RegionFile::open(&path.join("r.0.0.rg")).unwrap().contains(1, 0));
+
+    // the truncated chunk is still quarantined once loaded
+    assert!(is_regenerated(&load_chunk(&world, 0, 0)));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(quarantined(&errors[0], 0, 0).exists());
+}
+
+#[test]
+fn unreadable_region_is_quarantined() {
+    let path = fixture_world("truncated_header");
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    assert!(is_regenerated(&load_chunk(&world, 0, 0)));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+
+    let quarantine = quarantined(&errors[0], 0, 0);
+    assert_eq!(fs::read(&quarantine).unwrap(), fs::read(Path::new(FIXTURES).join("truncated_header/r.0.0.rg")).unwrap());
+
+    world.write().unwrap().set_block_at_coords(1, 100, 1, Block::Sand);
+    World::save(&world);
+    assert!(RegionFile::open(&path.join("r.0.0.rg")).unwrap().contains(0, 0));
+}
+
+#[test]
+fn truncated_legacy_chunk_is_quarantined() {
+    let path = fixture_world("truncated_legacy");
+    let world = create_world(path.to_str().unwrap(), &WorldMetadata::new(SEED, "flat")).unwrap();
+
+    assert!(is_regenerated(&load_chunk(&world, 0, 0)));
+
+    let errors = world.read().unwrap().errors();
+    assert_eq!(errors.len(), 1);
+    assert!(quarantined(&errors[0], 0, 0).exists());
+    assert!(!path.join("0_0.ck").exists());
+}
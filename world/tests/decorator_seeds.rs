@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use nalgebra::{Vector2, Vector3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use world::generator::decorators::{decorator_random, DecorationContext, DecoratorTowerPlant};
+use world::{BiomeType, Block, Chunk};
+
+const SEED: i64 = 42;
+const GRID: i32 = 32;
+const DRAWS: usize = 64;
+
+fn draws(mut random: StdRng) -> Vec<u64> {
+    (0..DRAWS).map(|_| random.gen()).collect()
+}
+
+fn stream(seed: i64, x: i32, z: i32, decorator: usize) -> Vec<u64> {
+    draws(decorator_random(seed, Vector2::new(x, z), BiomeType::Forest, decorator))
+}
+
+/**
+ * Fraction of equal bits, and correlation of the values (as uniform floats), between paired streams
+ */
+fn compare(pairs: &[(Vec<u64>, Vec<u64>)]) -> (f64, f64) {
+    let mut equal_bits = 0u64;
+    let mut values = Vec::new();
+
+    for (a, b) in pairs {
+        for (x, y) in a.iter().zip(b) {
+            equal_bits += (!(x ^ y)).count_ones() as u64;
+            values.push(((x >> 11) as f64 / (1u64 << 53) as f64, (y >> 11) as f64 / (1u64 << 53) as f64));
+        }
+    }
+
+    let n = values.len() as f64;
+    let (mean_x, mean_y) = values.iter().fold((0., 0.), |(sx, sy), (x, y)| (sx + x / n, sy + y / n));
+
+    let (mut cov, mut var_x, mut var_y) = (0., 0., 0.);
+
+    for (x, y) in &values {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+
+    (equal_bits as f64 / (n * 64.), cov / (var_x * var_y).sqrt())
+}
+
+fn assert_independent(pairs: &[(Vec<u64>, Vec<u64>)]) {
+    let (equal_bits, correlation) = compare(pairs);
+    let n = (pairs.len() * DRAWS) as f64;
+
+    // both are about 4.5 standard deviations away from what independent streams give
+    assert!((equal_bits - 0.5).abs() < 4.5 * 0.5 / (n * 64.).sqrt(), "equal bits: {}", equal_bits);
+    assert!(correlation.abs() < 4.5 / n.sqrt(), "correlation: {}", correlation);
+}
+
+#[test]
+fn neighbouring_chunks_get_independent_streams() {
+    let mut pairs = Vec::new();
+
+    for x in -GRID / 2..GRID / 2 {
+        for z in -GRID / 2..GRID / 2 {
+            let center = stream(SEED, x, z, 0);
+
+            pairs.push((center.clone(), stream(SEED, x + 1, z, 0)));
+            pairs.push((center.clone(), stream(SEED, x, z + 1, 0)));
+            pairs.push((center, stream(SEED, x + 1, z + 1, 0)));
+        }
+    }
+
+    assert_independent(&pairs);
+}
+
+#[test]
+fn decorators_of_a_chunk_get_independent_streams() {
+    let pairs: Vec<_> = (0..GRID * GRID)
+        .map(|i| (stream(SEED, i % GRID, i / GRID, 0), stream(SEED, i % GRID, i / GRID, 1)))
+        .collect();
+
+    assert_independent(&pairs);
+}
+
+#[test]
+fn close_inputs_get_independent_streams() {
+    let mut pairs = Vec::new();
+
+    for i in 0..GRID * GRID {
+        let (x, z) = (i % GRID, i / GRID);
+
+        // swapped coordinates, and world seeds only differing in their high bits
+        pairs.push((stream(SEED, x, z + GRID, 0), stream(SEED, z + GRID, x, 0)));
+        pairs.push((stream(SEED, x, z, 0), stream(SEED + (1 << 40), x, z, 0)));
+    }
+
+    assert_independent(&pairs);
+}
+
+/**
+ * Sand everywhere, the first columns of the chunk (0, _) are a desert
+ */
+fn desert_border() -> HashMap<(i32, i32), Arc<Chunk>> {
+    let mut neighbourhood = HashMap::new();
+
+    for cx in -1..=1 {
+        for cz in -1..=1 {
+            let mut chunk = Chunk::new(cx, cz);
+
+            for x in 0..16 {
+                for z in 0..16 {
+                    let desert = cx == 0 && x < 4;
+
+                    *chunk.biome_at_mut(x, z) = if desert { BiomeType::Desert } else { BiomeType::Savanna };
+                    chunk.set_block_at_chunk(x, 63, z, Block::Sand);
+                }
+            }
+
+            neighbourhood.insert((cx, cz), Arc::new(chunk));
+        }
+    }
+
+    neighbourhood
+}
+
+#[test]
+fn cactus_stay_in_their_biome() {
+    let neighbourhood = desert_border();
+    let cactus = DecoratorTowerPlant::cactus(30);
+    let mut placed = 0;
+
+    for seed in 0..64 {
+        let mut context = DecorationContext::new(0, 0, &neighbourhood);
+        context.set_biome(BiomeType::Desert);
+        cactus.decorate(&mut context, &mut StdRng::seed_from_u64(seed), Vector3::new(0, 0, 0));
+
+        for (position, block) in context.into_writes() {
+            assert_eq!(block, Block::Cactus);
+            assert!(position.x >= 0 && position.x < 4, "cactus placed at {:?} outside the desert", position);
+            placed += 1;
+        }
+    }
+
+    assert!(placed > 0);
+}
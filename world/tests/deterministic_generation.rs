@@ -0,0 +1,36 @@
+use std::collections::{HashMap, HashSet};
+
+use world::generator::{ChunkPipeline, GeneratorSettings};
+
+const SEED: i64 = 1234;
+
+fn grid() -> Vec<(i32, i32)> {
+    (-2..2).flat_map(|x| (-2..2).map(move |z| (x, z))).collect()
+}
+
+#[test]
+fn generation_order_does_not_change_chunks() {
+    // one chunk at a time, evicting cached stages as the chunk manager does
+    let mut pipeline = ChunkPipeline::new(SEED, "layered", &GeneratorSettings::default()).unwrap();
+    let mut one_by_one = HashMap::new();
+    let mut loaded = HashSet::new();
+
+    for &(x, z) in &grid() {
+        for chunk in pipeline.generate(&[(x, z)]).unwrap() {
+            one_by_one.insert((x, z), chunk.to_bytes());
+        }
+
+        loaded.insert((x, z));
+        pipeline.evict(&loaded);
+    }
+
+    // the whole grid at once, in reverse order
+    let mut reversed = grid();
+    reversed.reverse();
+
+    let chunks = ChunkPipeline::new(SEED, "layered", &GeneratorSettings::default()).unwrap().generate(&reversed).unwrap();
+
+    for (coords, chunk) in reversed.iter().zip(chunks) {
+        assert!(one_by_one[coords] == chunk.to_bytes(), "chunk {:?} differs", coords);
+    }
+}
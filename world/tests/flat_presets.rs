@@ -0,0 +1,66 @@
+use world::generator::{ChunkPipeline, FlatPreset, GeneratorSettings, FLAT_PRESETS};
+use world::{BiomeType, Block, WorldMetadata};
+
+const SEED: i64 = 1234;
+
+fn pipeline(preset: FlatPreset) -> Result<ChunkPipeline, Box<dyn std::error::Error>> {
+    let settings = GeneratorSettings {
+        flat: preset,
+        ..GeneratorSettings::default()
+    };
+
+    ChunkPipeline::new(SEED, "flat", &settings)
+}
+
+#[test]
+fn presets_are_valid() {
+    for name in FLAT_PRESETS {
+        let preset = FlatPreset::preset(name).unwrap();
+
+        let height: usize = preset.layers.iter().map(|(_, thickness)| thickness).sum();
+        let top = preset.layers.last().unwrap().0;
+
+        let chunk = &pipeline(preset).unwrap().generate(&[(0, 0)]).unwrap()[0];
+        assert_eq!(chunk.block_at_chunk(0, height as i32 - 1, 0), top, "{}", name);
+    }
+
+    assert_eq!(FlatPreset::preset("classic").unwrap(), FlatPreset::default());
+    assert!(FlatPreset::preset("unknown").is_none());
+}
+
+#[test]
+fn layers_are_stacked_from_the_bottom() {
+    let preset = FlatPreset::from_yaml("layers: [[Stone, 2], [Dirt, 3], [Water, 1]]\nbiome: Desert").unwrap();
+    let chunk = &pipeline(preset).unwrap().generate(&[(-1, 2)]).unwrap()[0];
+
+    for x in 0..16 {
+        for z in 0..16 {
+            let column: Vec<Block> = (0..7).map(|y| chunk.block_at_chunk(x, y, z)).collect();
+
+            assert_eq!(column, [Block::Stone, Block::Stone, Block::Dirt, Block::Dirt, Block::Dirt, Block::Water, Block::Air]);
+            assert_eq!(*chunk.biome_at(x, z), BiomeType::from_name("Desert").unwrap());
+        }
+    }
+}
+
+#[test]
+fn invalid_presets_are_rejected() {
+    assert!(FlatPreset::from_yaml("layers: [[Unknown, 2]]").is_err());
+    assert!(pipeline(FlatPreset::from_yaml("layers: [[Stone, 257]]").unwrap()).is_err());
+    assert!(pipeline(FlatPreset::from_yaml("layers: [[Stone, 1]]\nbiome: Unknown").unwrap()).is_err());
+}
+
+#[test]
+fn preset_is_stored_in_the_world_metadata() {
+    let mut metadata = WorldMetadata::new(SEED, "flat");
+    metadata.generator_settings.flat = FlatPreset::preset("water").unwrap();
+
+    let path = std::env::temp_dir().join(format!("glopr-flat-presets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+
+    metadata.save(path.to_str().unwrap()).unwrap();
+    let loaded = WorldMetadata::load(path.to_str().unwrap()).unwrap().unwrap();
+
+    assert_eq!(loaded.generator_settings, metadata.generator_settings);
+    std::fs::remove_dir_all(&path).unwrap();
+}
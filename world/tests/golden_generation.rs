@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use world::generator::layers::{Layer, LayerConfig};
+use world::generator::{ChunkGenerator, ChunkPipeline, GeneratorSettings, DEFAULT_GENERATOR};
+use world::{Chunk, MAX_HEIGHT};
+
+const GOLDEN_FILE: &str = "tests/golden/generation.txt";
+
+const SEEDS: [i64; 3] = [0, 42, -918_273_645];
+
+/** Chunks spread over positive and negative coordinates, and far from the origin */
+const CHUNKS: [(i32, i32); 4] = [(0, 0), (-1, 2), (17, -33), (-250, -4)];
+
+/** Corner of the 3x3 grid of chunks generated by the whole pipeline: caves and decorators */
+const PIPELINE_GRID: (i32, i32) = (-1, -1);
+
+/** Layer areas (x, z, size) in blocks */
+const LAYER_AREAS: [(isize, isize, usize); 2] = [(0, 0, 64), (-3000, 1700, 64)];
+
+/**
+ * 64 bits FNV-1a: unlike the std hashers, it is guaranteed to be stable
+ */
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+fn hash_chunk(chunk: &Chunk) -> (u64, u64, u64) {
+    let mut blocks = Fnv::new();
+    let mut biomes = Fnv::new();
+    let mut grass = Fnv::new();
+
+    for x in 0..16 {
+        for z in 0..16 {
+            for y in 0..MAX_HEIGHT {
+                blocks.write(&(chunk.block_at_chunk(x, y, z) as u16).to_le_bytes());
+            }
+
+            biomes.write(&(chunk.biome_at(x, z).id() as i64).to_le_bytes());
+        }
+    }
+
+    for color in &chunk.grass_color {
+        for i in 0..4 {
+            grass.write(&color[i].to_bits().to_le_bytes());
+        }
+    }
+
+    (blocks.0, biomes.0, grass.0)
+}
+
+fn hash_layer(layer: &mut Layer, (x, z, size): (isize, isize, usize)) -> u64 {
+    let mut hash = Fnv::new();
+
+    for value in layer.generate(x, z, size, size).data {
+        hash.write(&(value as i64).to_le_bytes());
+    }
+
+    hash.0
+}
+
+fn compute_hashes() -> BTreeMap<String, u64> {
+    let mut hashes = BTreeMap::new();
+
+    for &seed in &SEEDS {
+        let mut generator = ChunkGenerator::new(seed);
+
+        for &(x, z) in &CHUNKS {
+            let (blocks, biomes, grass) = hash_chunk(&generator.generate_xz(x, z));
+
+            hashes.insert(format!("seed={} chunk={},{} blocks", seed, x, z), blocks);
+            hashes.insert(format!("seed={} chunk={},{} biomes", seed, x, z), biomes);
+            hashes.insert(format!("seed={} chunk={},{} grass", seed, x, z), grass);
+        }
+
+        let mut pipeline = ChunkPipeline::new(seed, DEFAULT_GENERATOR, &GeneratorSettings::default()).unwrap();
+        let grid: Vec<_> = (0..9).map(|i| (PIPELINE_GRID.0 + i % 3, PIPELINE_GRID.1 + i / 3)).collect();
+
+        for chunk in pipeline.generate(&grid).unwrap() {
+            let (x, z) = (chunk.coords().x, chunk.coords().y);
+            let (blocks, _, _) = hash_chunk(&chunk);
+
+            hashes.insert(format!("seed={} decorated={},{} blocks", seed, x, z), blocks);
+        }
+
+        let (mut biomes, mut zoomed) = LayerConfig::preset("default").unwrap().build(seed).unwrap().into_generators();
+
+        for &area in &LAYER_AREAS {
+            let (x, z, size) = area;
+
+            hashes.insert(format!("seed={} layer=biomes area={},{},{}", seed, x, z, size), hash_layer(&mut biomes, area));
+            hashes.insert(format!("seed={} layer=zoomed area={},{},{}", seed, x, z, size), hash_layer(&mut zoomed, area));
+        }
+    }
+
+    hashes
+}
+
+fn format_hashes(hashes: &BTreeMap<String, u64>) -> String {
+    hashes.iter().map(|(key, hash)| format!("{} {:016x}\n", key, hash)).collect()
+}
+
+/**
+ * Pins the output of world generation: a fixed set of chunks, a grid of decorated chunks and
+ * biome layer areas are generated for several seeds and hashed, hashes are compared to the
+ * golden file.
+ * After an intended change of the generation, bless the new hashes with:
+ *     BLESS=1 cargo test -p world --test golden_generation
+ */
+#[test]
+fn generation_matches_golden_hashes() {
+    let hashes = compute_hashes();
+
+    if std::env::var_os("BLESS").is_some() {
+        fs::write(GOLDEN_FILE, format_hashes(&hashes)).unwrap();
+        return;
+    }
+
+    let golden = fs::read_to_string(GOLDEN_FILE).expect("missing golden file, generate it with BLESS=1");
+    let golden: BTreeMap<&str, &str> = golden.lines().filter_map(|l| l.rsplit_once(' ')).collect();
+
+    let mut mismatches: Vec<String> = hashes
+        .iter()
+        .map(|(key, hash)| (key, golden.get(key.as_str()), format!("{:016x}", hash)))
+        .filter(|(_, expected, hash)| *expected != Some(&hash.as_str()))
+        .map(|(key, expected, hash)| format!("  {}: expected {}, got {}", key, expected.unwrap_or(&"nothing"), hash))
+        .collect();
+
+    mismatches.extend(golden.keys().filter(|k| !hashes.contains_key(**k)).map(|k| format!("  {}: no longer generated", k)));
+
+    assert!(
+        mismatches.is_empty(),
+        "world generation changed:\n{}\nif intended, bless the new hashes with `BLESS=1 cargo test -p world --test golden_generation`",
+        mismatches.join("\n")
+    );
+}
@@ -0,0 +1,100 @@
+use world::generator::layers::{LayerConfig, LayerResult, LAYER_PRESETS};
+use world::BiomeType;
+
+const SEED: i64 = 42;
+/** Area of the `biomes` layers, in cells of 4 blocks */
+const SIZE: usize = 256;
+
+fn biomes(preset: &str) -> LayerResult {
+    let (mut biomes, _) = LayerConfig::preset(preset).unwrap().build(SEED).unwrap().into_generators();
+
+    biomes.generate(-(SIZE as isize) / 2, -(SIZE as isize) / 2, SIZE, SIZE)
+}
+
+fn share(result: &LayerResult, filter: impl Fn(BiomeType) -> bool) -> f32 {
+    result.data.iter().filter(|&&v| filter(BiomeType::from_id(v))).count() as f32 / result.data.len() as f32
+}
+
+/**
+ * Share of the cells whose right neighbour has another biome
+ */
+fn borders(result: &LayerResult) -> f32 {
+    let changes = result.data.windows(2).filter(|w| w[0] != w[1]).count();
+
+    changes as f32 / result.data.len() as f32
+}
+
+#[test]
+fn presets_are_valid() {
+    for preset in LAYER_PRESETS {
+        let graph = LayerConfig::preset(preset).unwrap().build(SEED).unwrap();
+
+        assert_eq!(graph.stages()[0].name, "island");
+        assert_eq!(graph.stages().last().unwrap().scale, 1);
+    }
+
+    assert!(LayerConfig::preset("unknown").is_none());
+}
+
+#[test]
+fn default_preset_stages_match_its_generators() {
+    let (mut expected, _) = LayerConfig::preset("default").unwrap().build(SEED).unwrap().into_generators();
+    let mut graph = LayerConfig::preset("default").unwrap().build(SEED).unwrap();
+
+    assert_eq!(graph.stage("island").unwrap().scale, 2048);
+    assert_eq!(graph.stage("river_apply").unwrap().scale, 4);
+    assert_eq!(graph.generate("river_apply", 10, -20, 32, 32).unwrap().data, expected.generate(10, -20, 32, 32).data);
+
+    // intermediate stages
+    let groups = graph.generate("biome_group", 0, 0, 16, 16).unwrap();
+    assert!(groups.data.iter().all(|v| (0..=4).contains(v)));
+
+    assert!(graph.generate("unknown", 0, 0, 16, 16).is_none());
+}
+
+#[test]
+fn presets_change_biome_distribution() {
+    let default = biomes("default");
+
+    assert!(share(&default, |b| b == BiomeType::River) > 0.0);
+    assert_eq!(share(&biomes("no_rivers"), |b| b == BiomeType::River), 0.0);
+
+    assert!(share(&biomes("more_ocean"), |b| b.is_ocean()) > share(&default, |b| b.is_ocean()));
+    assert!(borders(&biomes("large_biomes")) < borders(&default) / 2.0);
+}
+
+#[test]
+fn cached_layers_match_uncached_layers() {
+    let config = LayerConfig::preset("default").unwrap();
+
+    let (mut biomes, mut zoomed) = config.clone().build(SEED).unwrap().into_generators();
+    let (mut expected_biomes, mut expected_zoomed) = config.without_caches().build(SEED).unwrap().into_generators();
+
+    // unaligned areas, over several tiles and twice to read cached tiles
+    for &(x, y, size) in &[(-70, 13, 10), (-75, 10, 40), (31, -33, 70), (-70, 13, 10)] {
+        assert_eq!(biomes.generate(x, y, size, size).data, expected_biomes.generate(x, y, size, size).data);
+        assert_eq!(
+            zoomed.generate(x * 4, y * 4, size, size).data,
+            expected_zoomed.generate(x * 4, y * 4, size, size).data
+        );
+    }
+}
+
+#[test]
+fn invalid_graphs_are_rejected() {
+    let build = |yaml: &str| LayerConfig::from_yaml(yaml).unwrap().build(SEED);
+
+    let header = "biomes: zoom\nzoomed: zoom\nlayers:\n    - { name: island, kind: island, salt: 1 }\n";
+
+    assert!(build(&format!("{}    - {{ name: zoom, kind: zoom, salt: 2 }}\n", header)).is_ok());
+    // unknown parent
+    assert!(build(&format!("{}    - {{ name: zoom, kind: zoom, salt: 2, parent: ocean }}\n", header)).is_err());
+    // missing source
+    assert!(build(&format!("{}    - {{ name: zoom, kind: hills, salt: 2 }}\n", header)).is_err());
+    // empty cache
+    assert!(build(&format!("{}    - {{ name: zoom, kind: zoom, salt: 2, cache: 0 }}\n", header)).is_err());
+    // duplicated layer
+    assert!(build(&format!("{}    - {{ name: island, kind: zoom, salt: 2 }}\n", header)).is_err());
+    // unknown output
+    assert!(build(header).is_err());
+}
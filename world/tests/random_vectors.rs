@@ -0,0 +1,31 @@
+use world::generator::{feature_seed, SimpleRandom, CAVE_FEATURE};
+
+/**
+ * Exact outputs for known seeds: they must be the same on every target, 32 bits ones included,
+ * also for seeds that don't fit in an i32
+ */
+#[test]
+fn simple_random_vectors() {
+    let vectors: [((i64, i64), [isize; 8]); 4] = [
+        ((101, 0), [132, 466, 540, 79, 298, 352, 168, 309]),
+        ((38127, 42), [273, 455, 873, 177, 930, 164, 223, 309]),
+        ((9282, -123456789), [630, 860, 495, 429, 439, 238, 881, 153]),
+        ((9282, 8_589_934_597), [310, 599, 208, 123, 581, 870, 429, 451]),
+    ];
+
+    for &((salt, seed), expected) in &vectors {
+        let mut random = SimpleRandom::new(salt, seed);
+        random.init_local(-17, 2048);
+
+        let values: Vec<isize> = (0..8).map(|_| random.next(1000)).collect();
+        assert_eq!(values, expected, "salt {}, seed {}", salt, seed);
+    }
+}
+
+#[test]
+fn feature_seed_vectors() {
+    assert_eq!(feature_seed(0, CAVE_FEATURE, 0, 0), 0x4531_ceff_787e_a8e3);
+    assert_eq!(feature_seed(42, CAVE_FEATURE, -3, 7), 0x3ecb_aceb_7ef4_b3fa);
+    assert_eq!(feature_seed(-123456789, CAVE_FEATURE, 1000, -1000), 0xb84c_b605_c1c7_fef3);
+    assert_eq!(feature_seed(8_589_934_597, CAVE_FEATURE, 1000, -1000), 0x14ae_39f0_f5c0_d0b7);
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use world::RegionFile;
+
+const SECTOR_SIZE: usize = 4096;
+/** Both header slots */
+const DATA_START: usize = 6 * SECTOR_SIZE;
+
+fn region_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("glopr-region-{}-{}.rg", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    path
+}
+
+/**
+ * Incompressible data, so that chunks span several sectors
+ */
+fn payload(seed: u64, size: usize) -> Vec<u8> {
+    let mut state = seed;
+
+    (0..size)
+        .map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+fn read(path: &Path, x: i32, z: i32) -> Option<Vec<u8>> {
+    RegionFile::open(path).unwrap().read_chunk(x, z).unwrap()
+}
+
+#[test]
+fn chunks_are_written_in_free_sectors() {
+    let path = region_path("free-sectors");
+    let mut region = RegionFile::open(&path).unwrap();
+
+    for x in 0..4 {
+        region.write_chunk(x, 0, &payload(x as u64, 2 * SECTOR_SIZE)).unwrap();
+    }
+
+    region.commit().unwrap();
+    let size = fs::metadata(&path).unwrap().len();
+
+    // rewriting the same chunks reuses the sectors freed by the previous commits
+    for i in 0..10 {
+        let mut region = RegionFile::open(&path).unwrap();
+
+        region.write_chunk(i % 4, 0, &payload(100 + i as u64, 2 * SECTOR_SIZE)).unwrap();
+        region.delete_chunk(5, 5);
+        region.commit().unwrap();
+    }
+
+    assert!(fs::metadata(&path).unwrap().len() <= size + 4 * 3 * SECTOR_SIZE as u64);
+
+    for x in 0..4 {
+        let last = (0..10).filter(|i| i % 4 == x).max().unwrap();
+        assert_eq!(read(&path, x, 0).unwrap(), payload(100 + last as u64, 2 * SECTOR_SIZE));
+    }
+
+    assert!(read(&path, 5, 5).is_none());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn interrupted_header_falls_back_to_the_previous_one() {
+    let path = region_path("interrupted");
+
+    let mut region = RegionFile::open(&path).unwrap();
+    region.write_chunk(0, 0, &payload(1, 1000)).unwrap();
+    region.commit().unwrap();
+
+    region.write_chunk(0, 0, &payload(2, 1000)).unwrap();
+    region.write_chunk(1, 0, &payload(3, 1000)).unwrap();
+    region.commit().unwrap();
+
+    assert_eq!(read(&path, 0, 0).unwrap(), payload(2, 1000));
+
+    // the last commit wrote the second slot: corrupt it as a crash during the write would
+    let mut data = fs::read(&path).unwrap();
+    data[DATA_START / 2 + 100] ^= 0xFF;
+    fs::write(&path, &data).unwrap();
+
+    assert_eq!(read(&path, 0, 0).unwrap(), payload(1, 1000));
+    assert!(read(&path, 1, 0).is_none());
+
+    // both slots corrupted
+    data[100] ^= 0xFF;
+    fs::write(&path, &data).unwrap();
+
+    assert!(RegionFile::open(&path).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn commits_update_the_file_in_place() {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = region_path("in-place");
+
+    let mut region = RegionFile::open(&path).unwrap();
+    region.write_chunk(0, 0, &payload(1, 1000)).unwrap();
+    region.commit().unwrap();
+
+    let inode = fs::metadata(&path).unwrap().ino();
+
+    region.write_chunk(1, 0, &payload(2, 1000)).unwrap();
+    region.commit().unwrap();
+
+    assert_eq!(fs::metadata(&path).unwrap().ino(), inode);
+    assert_eq!(read(&path, 0, 0).unwrap(), payload(1, 1000));
+    assert_eq!(read(&path, 1, 0).unwrap(), payload(2, 1000));
+    fs::remove_file(&path).unwrap();
+}
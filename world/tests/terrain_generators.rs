@@ -0,0 +1,87 @@
+use world::generator::{register_terrain_generator, terrain_generators, ChunkPipeline, FlatPreset, GeneratorSettings, TerrainGenerator};
+use world::{Block, Chunk};
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const SEED: i64 = 1234;
+
+/**
+ * Empty chunks, with a single block at the origin of the world
+ */
+struct OriginGenerator {}
+
+impl TerrainGenerator for OriginGenerator {
+    fn generate_xz(&mut self, x: i32, z: i32) -> Chunk {
+        let mut chunk = Chunk::new(x, z);
+
+        if (x, z) == (0, 0) {
+            chunk.set_block_at_chunk(0, 0, 0, Block::Stone);
+        }
+
+        chunk
+    }
+}
+
+#[test]
+fn builtin_generators_are_registered() {
+    let generators = terrain_generators();
+
+    assert_eq!(generators[..2], ["layered", "flat"]);
+    assert!(ChunkPipeline::new(SEED, "unknown", &GeneratorSettings::default()).is_err());
+
+    let chunks = ChunkPipeline::new(SEED, "flat", &GeneratorSettings::default()).unwrap().generate(&[(3, -2)]).unwrap();
+
+    assert_eq!(chunks[0].block_at_chunk(5, 3, 5), Block::Grass);
+    assert_eq!(chunks[0].block_at_chunk(5, 4, 5), Block::Air);
+}
+
+#[test]
+fn generators_can_be_registered() {
+    register_terrain_generator("origin", |_, _| Ok(Box::new(OriginGenerator {}))).unwrap();
+
+    assert!(terrain_generators().contains(&"origin".to_string()));
+    assert!(register_terrain_generator("origin", |_, _| Ok(Box::new(OriginGenerator {}))).is_err());
+    assert!(register_terrain_generator("layered", |_, _| Ok(Box::new(OriginGenerator {}))).is_err());
+
+    let chunks = ChunkPipeline::new(SEED, "origin", &GeneratorSettings::default()).unwrap().generate(&[(0, 0), (1, 0)]).unwrap();
+
+    assert_eq!(chunks[0].block_at_chunk(0, 0, 0), Block::Stone);
+    assert_eq!(chunks[1].block_at_chunk(0, 0, 0), Block::Air);
+}
+
+#[test]
+fn settings_are_checked_without_a_generator() {
+    let mut settings = GeneratorSettings::default();
+
+    assert!(settings.check("layered").is_ok());
+    assert!(settings.check("flat").is_ok());
+
+    settings.terrain.layers = "unknown_layers.yaml".to_string();
+    settings.flat = FlatPreset::from_yaml("layers: [[Stone, 257]]").unwrap();
+
+    assert!(settings.check("layered").is_err());
+    assert!(settings.check("flat").is_err());
+
+    // sections of other generators are unknown
+    assert!(settings.check("origin").is_ok());
+}
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn generators_are_reused() {
+    register_terrain_generator("counted", |_, _| {
+        CREATED.fetch_add(1, Ordering::SeqCst);
+        Ok(Box::new(OriginGenerator {}))
+    }).unwrap();
+
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+    let mut pipeline = ChunkPipeline::new(SEED, "counted", &GeneratorSettings::default()).unwrap();
+
+    for x in 0..10 {
+        assert!(pool.install(|| pipeline.generate(&[(x * 10, 0), (x * 10, 10)]).is_ok()));
+    }
+
+    // the splits of a stage share the generators of the pipeline instead of creating their own
+    assert!(CREATED.load(Ordering::SeqCst) <= 3);
+}
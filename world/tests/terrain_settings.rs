@@ -0,0 +1,69 @@
+use world::generator::{ChunkGenerator, ChunkPipeline, GeneratorSettings, NoiseConfig, TerrainSettings, TERRAIN_PRESETS};
+use world::{Block, Chunk};
+
+const SEED: i64 = 1234;
+
+fn blocks(chunk: &Chunk) -> Vec<Block> {
+    (0..256).flat_map(|y| (0..16).flat_map(move |x| (0..16).map(move |z| chunk.block_at_chunk(x, y, z)))).collect()
+}
+
+#[test]
+fn presets_are_valid() {
+    for name in TERRAIN_PRESETS {
+        let settings = TerrainSettings::preset(name).unwrap();
+
+        assert!(ChunkGenerator::with_settings(SEED, &settings).is_ok(), "{}", name);
+    }
+
+    assert_eq!(TerrainSettings::preset("default").unwrap(), TerrainSettings::default());
+    assert!(TerrainSettings::preset("unknown").is_none());
+}
+
+#[test]
+fn default_settings_keep_the_terrain() {
+    let settings = TerrainSettings::from_yaml("base_size: 8.5").unwrap();
+    assert_eq!(settings, TerrainSettings::default());
+
+    let expected = ChunkGenerator::new(SEED).generate_xz(3, -5);
+    let chunk = ChunkGenerator::with_settings(SEED, &settings).unwrap().generate_xz(3, -5);
+
+    assert!(blocks(&chunk) == blocks(&expected));
+}
+
+#[test]
+fn settings_change_the_terrain() {
+    let amplified = TerrainSettings::preset("amplified").unwrap();
+
+    let expected = ChunkGenerator::new(SEED).generate_xz(3, -5);
+    let chunk = ChunkGenerator::with_settings(SEED, &amplified).unwrap().generate_xz(3, -5);
+
+    assert!(blocks(&chunk) != blocks(&expected));
+}
+
+#[test]
+fn detail_noise_changes_the_terrain() {
+    let settings = TerrainSettings::from_yaml("detail_noise: { scale: { source: simplex, frequency: 0.05, amplitude: 20. } }").unwrap();
+    let noise = NoiseConfig::Scale { source: Box::new(NoiseConfig::Simplex), frequency: 0.05, amplitude: 20. };
+
+    assert_eq!(settings.detail_noise, Some(noise));
+
+    let expected = ChunkGenerator::new(SEED).generate_xz(3, -5);
+
+    for settings in &[settings, TerrainSettings::preset("rugged").unwrap()] {
+        let chunk = ChunkGenerator::with_settings(SEED, settings).unwrap().generate_xz(3, -5);
+        assert!(blocks(&chunk) != blocks(&expected));
+    }
+}
+
+#[test]
+fn invalid_settings_are_rejected() {
+    assert!(TerrainSettings::from_yaml("y_stretch: high").is_err());
+    assert!(TerrainSettings::from_yaml("detail_noise: { ridged: { source: worley, octaves: 4 } }").is_err());
+
+    let settings = GeneratorSettings {
+        terrain: TerrainSettings::from_yaml("layers: unknown_layers.yaml").unwrap(),
+        ..GeneratorSettings::default()
+    };
+
+    assert!(ChunkPipeline::new(SEED, "layered", &settings).is_err());
+}